@@ -1,7 +1,8 @@
 use crate::algebraic_structure::{Element, HasAdd, HasMul, HasRepresentation, HasSub};
 use crate::integers::integer_computations::{extended_euclidean_ordered, extended_euclidean_to_integers, pow_rug};
+use crate::integers::prime::baillie_psw_is_prime;
 use rug::ops::SubFrom;
-use rug::{integer::IsPrime, Complete, Integer};
+use rug::{Complete, Integer};
 use std::sync::Arc;
 
 
@@ -76,7 +77,7 @@ impl HasSub for FiniteField {
 
 impl FiniteField {
     pub fn new(size: Integer) -> Option<FiniteField> {
-        if size.is_probably_prime(30) != IsPrime::No {
+        if baillie_psw_is_prime(&size) {
             Some(FiniteField {
                 size,
             })
@@ -131,6 +132,27 @@ impl FiniteField {
 #[derive(Debug, Clone)]
 pub struct MultiplicativeGroup {
     mod_num: Integer,
+    subgroup: Option<Subgroup>,
+}
+
+
+// A cyclic subgroup of prime order, as used for Diffie-Hellman and Schnorr/DSA.
+#[derive(Debug, Clone)]
+pub struct Subgroup {
+    order: Integer,
+    generator: Integer,
+}
+
+
+impl Subgroup {
+    pub fn get_order(&self) -> &Integer {
+        &self.order
+    }
+
+
+    pub fn get_generator(&self) -> &Integer {
+        &self.generator
+    }
 }
 
 
@@ -170,6 +192,7 @@ impl MultiplicativeGroup {
     pub fn new(mod_num: Integer) -> MultiplicativeGroup {
         MultiplicativeGroup {
             mod_num,
+            subgroup: None,
         }
     }
 
@@ -178,10 +201,25 @@ impl MultiplicativeGroup {
         let mod_num = finite_field.mod_num().clone();
         MultiplicativeGroup {
             mod_num,
+            subgroup: None,
+        }
+    }
+
+
+    // The generator is expected to have the given order modulo mod_num.
+    pub fn with_subgroup(mod_num: Integer, order: Integer, generator: Integer) -> MultiplicativeGroup {
+        MultiplicativeGroup {
+            mod_num,
+            subgroup: Some(Subgroup { order, generator }),
         }
     }
 
 
+    pub fn get_subgroup(&self) -> Option<&Subgroup> {
+        self.subgroup.as_ref()
+    }
+
+
     pub fn mod_num(&self) -> &Integer {
         &self.mod_num
     }
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+use beralg::integers::pseudoprimes::{chernick_carmichael_numbers, erdos_carmichael_numbers, lucas_pseudoprimes, strong_pseudoprimes};
 use rand::{thread_rng, Rng};
 use rug::integer::IsPrime;
 use rug::{Complete, Integer};
@@ -62,29 +64,52 @@ fn generate_non_primes() {
 }
 
 
-fn generate_small_primes(n: usize) {
-    println!("Opening file ./small-primes");
+fn write_numbers<T: std::fmt::Display>(path: &str, numbers: &[T]) {
+    println!("Opening file ./{}", path);
     let mut file = fs::OpenOptions::new()
         .create(true)
         .write(true)
         .truncate(true)
-        .open("small-primes")
+        .open(path)
         .unwrap();
 
-    let mut p: Integer = Integer::from(2);
-    
-    println!("Generating list of small primes...");
-    while p < n {
-        if let Err(e) = writeln!(file, "{}", &p) {
+    for n in numbers {
+        if let Err(e) = writeln!(file, "{}", n) {
             eprintln!("Couldn't write to file: {}", e);
         }
-        p.next_prime_mut();
     }
 }
 
 
+// Carmichael numbers fool the Fermat test for every base coprime to them.
+fn generate_carmichael_numbers() {
+    println!("Generating list of Carmichael numbers...");
+    let mut numbers = chernick_carmichael_numbers(3, 300);
+    numbers.extend(chernick_carmichael_numbers(4, 100));
+    numbers.extend(chernick_carmichael_numbers(5, 100));
+    // 720720 = 2^4 * 3^2 * 5 * 7 * 11 * 13 has enough divisors d with d + 1 prime to find many quickly.
+    numbers.extend(erdos_carmichael_numbers(720720, 200, 1000000));
+    write_numbers("carmichael-numbers", &numbers);
+}
+
+
+fn generate_strong_pseudoprimes() {
+    println!("Generating list of strong pseudoprimes...");
+    // Includes the strong pseudoprimes to the bases 2, 3 and 2, 3, 5 below the bound.
+    write_numbers("strong-pseudoprimes", &strong_pseudoprimes(&[2], 250000000));
+}
+
+
+fn generate_lucas_pseudoprimes() {
+    println!("Generating list of Lucas pseudoprimes...");
+    write_numbers("lucas-pseudoprimes", &lucas_pseudoprimes(1000000, false));
+}
+
+
 fn main() {
     // generate_primes();
     // generate_non_primes();
-    generate_small_primes(10000000);
+    // generate_carmichael_numbers();
+    // generate_strong_pseudoprimes();
+    // generate_lucas_pseudoprimes();
 }
@@ -2,9 +2,9 @@
 use std::{sync::Arc, time::Duration};
 use std::time::Instant;
 use beralg::{algebraic_structure::{finite_field::FiniteField, Element}, integers::integer_computations::naive_pow};
-use rug::integer::IsPrime;
-use rug::ops::PowAssign;
-use rug::Integer;
+use beralg::integers::prime::rabin_miller_is_prime;
+use beralg::integers::special_form::{find_proth_prime_with_bit_length, lucas_lehmer_is_prime, mersenne_number, mersenne_prime_exponents, proth_is_prime};
+use rug::{Complete, Integer};
 use plotters::prelude::*;
 use plotters::coord::combinators::IntoLogRange;
 use beralg::random::{randint_bits, randint_digits};
@@ -55,10 +55,8 @@ fn check_timing_against_naive(a: &Integer, b: &Integer, p: &Integer, n: usize) -
 
 fn plot_timing_naive_square(n: usize, m: usize) -> Result<(), Box<dyn std::error::Error>> {
     let mut q = Integer::from(17);
-    let mut p = Integer::from(2);
-    p.pow_assign(127);
-    p = p-1;
-    assert!(p.is_probably_prime(20) != IsPrime::No);
+    assert!(lucas_lehmer_is_prime(127));
+    let p = mersenne_number(127);
     // let mut rng = RandState::new();
     let mut naive_vec: Vec<(u128, u128)> = Vec::new();
     let mut square_vec: Vec<(u128, u128)> = Vec::new();
@@ -155,10 +153,8 @@ fn plot_timing_naive_square(n: usize, m: usize) -> Result<(), Box<dyn std::error
 
 fn plot_timing_naive(n: usize, m: usize) -> Result<(), Box<dyn std::error::Error>> {
     let mut p = Integer::ONE.clone();
-    let mut q = Integer::from(2);
-    q.pow_assign(127);
-    q = q-1;
-    assert!(q.is_probably_prime(20) != IsPrime::No);
+    assert!(lucas_lehmer_is_prime(127));
+    let q = mersenne_number(127);
     // let mut rng = RandState::new();
     let mut naive_vec: Vec<(u128, u128)> = Vec::new();
     let mut max_time_naive = 0;
@@ -233,10 +229,8 @@ fn plot_timing_naive(n: usize, m: usize) -> Result<(), Box<dyn std::error::Error
 
 fn plot_timing_square(n: usize, m: usize) -> Result<(), Box<dyn std::error::Error>> {
 
-    let mut q = Integer::from(2);
-    q.pow_assign(127);
-    q = q-1;
-    assert!(q.is_probably_prime(20) != IsPrime::No);
+    assert!(lucas_lehmer_is_prime(127));
+    let q = mersenne_number(127);
     let mut p = Integer::ONE.clone();
     // let mut rng = RandState::new();
     let mut square_vec: Vec<(u64, u64)> = Vec::new();
@@ -310,6 +304,90 @@ fn plot_timing_square(n: usize, m: usize) -> Result<(), Box<dyn std::error::Erro
 }
 
 
+// Average time in microseconds of `test` over n runs.
+fn time_test<F: Fn() -> bool>(test: F, n: usize) -> u64 {
+    let now = Instant::now();
+    for _ in 0..n {
+        assert!(test());
+    }
+    (now.elapsed().as_micros()/n as u128) as u64
+}
+
+
+// Lucas-Lehmer on the Mersenne primes and Proth's test on random Proth primes, both against
+// rabin_miller_is_prime with t rounds on the same primes.
+fn plot_timing_special_form_tests(t: usize, m: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let mut lucas_lehmer_vec: Vec<(u32, u64)> = Vec::new();
+    let mut rabin_miller_mersenne_vec: Vec<(u32, u64)> = Vec::new();
+    for p in mersenne_prime_exponents(2300).into_iter().filter(|p| *p >= 61) {
+        let mersenne = mersenne_number(p);
+        lucas_lehmer_vec.push((p, time_test(|| lucas_lehmer_is_prime(p), m)));
+        rabin_miller_mersenne_vec.push((p, time_test(|| rabin_miller_is_prime(&mersenne, t), m)));
+    }
+
+    let mut proth_vec: Vec<(u32, u64)> = Vec::new();
+    let mut rabin_miller_proth_vec: Vec<(u32, u64)> = Vec::new();
+    for bits in (128..=2048).step_by(128) {
+        let proth = find_proth_prime_with_bit_length(bits);
+        let n = (&proth - 1u32).complete().find_one(0).expect("Proth numbers are odd");
+        let k = (&proth - 1u32).complete() >> n;
+        proth_vec.push((bits as u32, time_test(|| proth_is_prime(&k, n), m)));
+        rabin_miller_proth_vec.push((bits as u32, time_test(|| rabin_miller_is_prime(&proth, t), m)));
+    }
+
+    let max_time = rabin_miller_mersenne_vec.iter().chain(&rabin_miller_proth_vec).map(|(_, time)| *time).max().unwrap_or(1);
+
+    let root = SVGBackend::new("../latex/proj1/images/special-form.svg", (600, 400)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Special Form Tests vs Rabin-Miller", ("computer-modern", 30).into_font())
+        .margin(40)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0..2300u32, (1..max_time).log_scale())?;
+
+    chart.configure_mesh()
+        .x_desc("Bits")
+        .x_label_style(("computer-modern", 12).into_font())
+        .y_desc("Microseconds")
+        .y_label_style(("computer-modern", 12).into_font())
+        .draw()?;
+
+    for (points, label, color) in [
+        (lucas_lehmer_vec, "Lucas-Lehmer", BLUE),
+        (rabin_miller_mersenne_vec, "Rabin-Miller (Mersenne)", RED),
+        (proth_vec, "Proth", GREEN),
+        (rabin_miller_proth_vec, "Rabin-Miller (Proth)", MAGENTA),
+    ] {
+        chart
+            .draw_series(PointSeries::of_element(
+                points.into_iter().map(|(bits, time)| (bits, time.max(1))),
+                3,
+                &color,
+                &|c, s, st| {
+                    EmptyElement::at(c) + Circle::new((0,0),s,st.filled())
+                },
+            ))?
+            .label(label)
+            .legend(move |(x, y)| Circle::new((x, y), 3, color.filled()));
+    }
+
+    chart
+        .configure_series_labels()
+        .label_font(("computer-modern", 12).into_font())
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .legend_area_size(12)
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+
 fn main() {
     let loops = 10;
     let naive_square_points: usize = 20;
@@ -318,4 +396,5 @@ fn main() {
     plot_timing_naive_square(naive_square_points, loops).expect("Should not fail");
     plot_timing_naive(naive_points, loops).expect("Should not fail");
     plot_timing_square(square_points, loops).expect("Should not fail");
+    plot_timing_special_form_tests(20, loops).expect("Should not fail");
 }
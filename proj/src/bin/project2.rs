@@ -1,6 +1,9 @@
 #![allow(dead_code)]
-use beralg::integers::prime::{find_prime_in_interval_with_sieving, find_prime_with_bit_length, find_prime_with_bit_length_using_sieving, find_prime_with_bit_length_using_trial_division, is_likely_prime_with_trial_division, rabin_miller_is_prime};
-use beralg::random::{randint_bits, randint_bits_odd};
+use beralg::integers::prime::{find_prime_in_interval_with_sieving, find_prime_with_bit_length, find_prime_with_bit_length_using_sieving, find_prime_with_bit_length_using_trial_division, find_prime_with_bit_length_with_test, is_likely_prime_with_trial_division, rabin_miller_is_prime};
+use beralg::integers::primality_test::{RabinMiller, TrialDivision};
+use beralg::integers::prime_counting::{li, prime_pi};
+use beralg::integers::prime_statistics::{expected_candidates, width_in_random_interval_search, PrimeSearchStatistics};
+use beralg::random::randint_bits;
 use rug::Integer;
 use std::time::Instant;
 use std::io::{BufRead, BufReader, Write};
@@ -9,52 +12,20 @@ use std::str::FromStr;
 
 
 fn count_candidates_in_find_prime_with_bit_length(bits: usize, t: usize, bound: usize) -> usize {
-    let mut p: Integer = randint_bits_odd(bits);
-    let mut count = 1;
-    while !is_likely_prime_with_trial_division(&p, t, bound) {
-        count += 1;
-        p = randint_bits_odd(bits);
-    }
-    count
+    let mut statistics = PrimeSearchStatistics::new();
+    let test = TrialDivision::new(bound as u64, RabinMiller::new(t));
+    find_prime_with_bit_length_with_test(bits, &test, Some(&mut statistics));
+    statistics.candidates
 }
 
 
 fn count_candidates_in_find_prime_with_bit_length_avg(bits: usize, n: usize, t: usize, bound: usize) -> f64 {
-    let mut count = 0;
+    let mut statistics = PrimeSearchStatistics::new();
+    let test = TrialDivision::new(bound as u64, RabinMiller::new(t));
     for _ in 0..n {
-        count += count_candidates_in_find_prime_with_bit_length(bits, t, bound);
-    }
-    (count as f64)/(n as f64)
-}
-
-
-fn width_in_random_interval_search(bits: usize, probability: f64) -> usize {
-    let mut d = 1;
-    while (1f64 - 1f64/((bits as f64)*2f64.ln())).powf(d as f64) > 1f64 - probability {
-        d += 1;
-    }
-    d
-}
-
-
-fn expected_candidates(bits: usize) -> f64 {
-    (bits as f64)*2f64.ln()
-}
-
-
-fn expected_candidates_with_filtration(bits: usize, bound: usize) -> f64 {
-    let small_primes = fs::File::open("small-primes").expect("small-primes file should have been generated");
-    let reader = BufReader::new(small_primes);
-    let mut prod: f64 = 1.;
-    
-    for prime in reader.lines() {
-        let p: Integer = Integer::from_str(&prime.unwrap()).expect("All entries of small-primes should be integers.");
-        if p < bound {
-            prod *= (p.to_f64()-1.)/p.to_f64();
-        }
+        find_prime_with_bit_length_with_test(bits, &test, Some(&mut statistics));
     }
-
-    (bits as f64)*2f64.ln()*prod
+    statistics.average_candidates()
 }
 
 
@@ -219,6 +190,21 @@ fn time_finding_primes_trial_division_vs_sieving() {
 }
 
 
+// Compares the density 1/expected_candidates(bits) used above with the exact fraction of primes
+// among the integers of `bits` bits, and with the li(x) estimate of the same count.
+fn compare_prime_density_with_exact_counts() {
+    for bits in 10..=46 {
+        let low = 1u64 << (bits - 1);
+        let high = 1u64 << bits;
+        let exact = prime_pi(high - 1) - prime_pi(low - 1);
+        let estimate = li(high as f64) - li(low as f64);
+        let exact_density = exact as f64/low as f64;
+        println!("bits: {}, primes: {}, li: {:.0}, density: {:.6}, 1/expected_candidates: {:.6}",
+            bits, exact, estimate, exact_density, 1./expected_candidates(bits));
+    }
+}
+
+
 fn main() {
     time_finding_primes_trial_division_vs_sieving();
 }
@@ -1,5 +1,100 @@
 use beralg::factor::{file_handler::choose_primes, random_squares::{factorization_by_random_squares, find_factors_by_random_squares, find_multiple_relations, find_squares_by_relations, find_two_real_factors_by_random_squares, merge_tuples}};
-use rug::Integer;
+use beralg::factor::continued_fraction::find_factor_by_continued_fraction;
+use beralg::factor::file_handler::number_of_chosen_primes;
+use beralg::factor::quadratic_sieve::find_factor_by_quadratic_sieve;
+use beralg::integers::prime::find_prime_with_bit_length;
+use plotters::prelude::*;
+use plotters::coord::combinators::IntoLogRange;
+use rug::{Complete, Integer};
+use std::time::Instant;
+
+
+// Average time in milliseconds for `method` to split m products of two primes with about half of
+// the digits each.
+fn time_factoring<F: Fn(&Integer) -> Integer>(method: F, digits: usize, m: usize) -> u64 {
+    let bits = (digits as f64*10f64.log2()/2.0).round() as usize;
+    let mut total = 0;
+    for _ in 0..m {
+        let p = find_prime_with_bit_length(bits, 30);
+        let q = find_prime_with_bit_length(bits, 30);
+        let n = (&p*&q).complete();
+        let now = Instant::now();
+        let factor = method(&n);
+        total += now.elapsed().as_millis();
+        assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, n);
+    }
+    (total/m as u128) as u64
+}
+
+
+// The line from Dixon's random squares through the continued fraction method to the quadratic
+// sieve, each up to where it takes about a minute.
+fn plot_timing_factoring_methods(m: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let random_squares = |n: &Integer| find_two_real_factors_by_random_squares(n, number_of_chosen_primes() + 10).0;
+    let random_squares_vec: Vec<(u32, u64)> = (6..=16).step_by(2)
+        .map(|digits| (digits as u32, time_factoring(random_squares, digits, m)))
+        .collect();
+    let continued_fraction = |n: &Integer| find_factor_by_continued_fraction(n).expect("n is composite");
+    let continued_fraction_vec: Vec<(u32, u64)> = (10..=45).step_by(5)
+        .map(|digits| (digits as u32, time_factoring(continued_fraction, digits, m)))
+        .collect();
+    let quadratic_sieve = |n: &Integer| find_factor_by_quadratic_sieve(n).expect("n is composite");
+    let quadratic_sieve_vec: Vec<(u32, u64)> = (10..=65).step_by(5)
+        .map(|digits| (digits as u32, time_factoring(quadratic_sieve, digits, m)))
+        .collect();
+
+    let max_time = random_squares_vec.iter().chain(&continued_fraction_vec).chain(&quadratic_sieve_vec).map(|(_, time)| *time).max().unwrap_or(1);
+
+    std::fs::create_dir_all("images")?;
+    let root = SVGBackend::new("images/factoring-methods.svg", (600, 400)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Random Squares, CFRAC and the Quadratic Sieve", ("computer-modern", 30).into_font())
+        .margin(40)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0..70u32, (1..max_time).log_scale())?;
+
+    chart.configure_mesh()
+        .x_desc("Digits")
+        .x_label_style(("computer-modern", 12).into_font())
+        .y_desc("Milliseconds")
+        .y_label_style(("computer-modern", 12).into_font())
+        .draw()?;
+
+    for (points, label, color) in [
+        (random_squares_vec, "Random squares", BLUE),
+        (continued_fraction_vec, "CFRAC", RED),
+        (quadratic_sieve_vec, "Quadratic sieve", GREEN),
+    ] {
+        chart
+            .draw_series(PointSeries::of_element(
+                points.into_iter().map(|(digits, time)| (digits, time.max(1))),
+                3,
+                &color,
+                &|c, s, st| {
+                    EmptyElement::at(c) + Circle::new((0,0),s,st.filled())
+                },
+            ))?
+            .label(label)
+            .legend(move |(x, y)| Circle::new((x, y), 3, color.filled()));
+    }
+
+    chart
+        .configure_series_labels()
+        .label_font(("computer-modern", 12).into_font())
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .legend_area_size(12)
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
 
 fn main() {
     let n = Integer::from(2003u64*1064u64*3539u64*8539u64);
@@ -11,4 +106,6 @@ fn main() {
         print!("{}, ", factor);
     }
     println!();
+
+    plot_timing_factoring_methods(3).expect("Should not fail");
 }
@@ -1,2 +1,17 @@
 pub mod file_handler;
 pub mod random_squares;
+pub mod factor_base;
+pub mod large_prime;
+pub mod relation_store;
+pub mod checkpoint;
+pub mod pollard_rho;
+pub mod pollard_p_minus_one;
+pub mod williams_p_plus_one;
+pub mod ecm;
+pub mod fermat;
+pub mod squfof;
+pub mod continued_fraction;
+pub mod quadratic_sieve;
+pub mod factorization;
+#[cfg(test)]
+pub(crate) mod test_helpers;
@@ -0,0 +1,198 @@
+use std::fs;
+use std::path::Path;
+use rug::Integer;
+
+
+// How the relations of a checkpoint were found, with the parameters of the factor base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    // Random squares over the chosen primes up to the bound.
+    RandomSquares { chosen_primes_bound: u64 },
+    QuadraticSieve { multiplier: u64, base_size: usize },
+}
+
+
+// (t, factors, large primes) as in PartialRelations.
+pub type SavedPartialRelation = (Integer, Vec<(u64, u64)>, (u64, u64));
+
+
+// The state of a factoring job, kept as text with one item per line:
+//      n <n>
+//      method random_squares <bound> | method quadratic_sieve <k> <size>
+//      large_primes <max_large_primes>
+//      target <number of relations>
+//      factor <factor found>
+//      remaining <composite left to split>
+//      relation <t> <p>^<e> ...
+//      partial <t> <P_1> <P_2> <p>^<e> ...
+// The factors and the remaining composites are the stages done so far, and the relations are
+// those of the last remaining composite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub n: Integer,
+    pub method: Method,
+    pub max_large_primes: usize,
+    pub target: usize,
+    pub factors: Vec<Integer>,
+    pub remaining: Vec<Integer>,
+    pub relations: Vec<(Integer, Vec<(u64, u64)>)>,
+    pub partials: Vec<SavedPartialRelation>,
+}
+
+
+fn write_factors(line: &mut String, factors: &[(u64, u64)]) {
+    for (p, e) in factors {
+        line.push_str(&format!(" {}^{}", p, e));
+    }
+    line.push('\n');
+}
+
+
+fn parse<T: std::str::FromStr>(word: Option<&str>, line: &str) -> Result<T, String> {
+    word.and_then(|word| word.parse().ok()).ok_or(format!("Could not read the line '{}'", line))
+}
+
+
+fn parse_factors<'a>(words: impl Iterator<Item = &'a str>, line: &str) -> Result<Vec<(u64, u64)>, String> {
+    words.map(|word| {
+        let (p, e) = word.split_once('^').ok_or(format!("Could not read the factor '{}' in '{}'", word, line))?;
+        Ok((parse(Some(p), line)?, parse(Some(e), line)?))
+    }).collect()
+}
+
+
+impl Checkpoint {
+    // A job which has only started on n.
+    pub fn new(n: &Integer, method: Method, max_large_primes: usize, target: usize) -> Checkpoint {
+        Checkpoint {
+            n: n.clone(),
+            method,
+            max_large_primes,
+            target,
+            factors: Vec::new(),
+            remaining: vec![n.clone()],
+            relations: Vec::new(),
+            partials: Vec::new(),
+        }
+    }
+
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("n {}\n", self.n);
+        text.push_str(&match self.method {
+            Method::RandomSquares { chosen_primes_bound } => format!("method random_squares {}\n", chosen_primes_bound),
+            Method::QuadraticSieve { multiplier, base_size } => format!("method quadratic_sieve {} {}\n", multiplier, base_size),
+        });
+        text.push_str(&format!("large_primes {}\ntarget {}\n", self.max_large_primes, self.target));
+        for factor in self.factors.iter() {
+            text.push_str(&format!("factor {}\n", factor));
+        }
+        for composite in self.remaining.iter() {
+            text.push_str(&format!("remaining {}\n", composite));
+        }
+        for (t, factors) in self.relations.iter() {
+            text.push_str(&format!("relation {}", t));
+            write_factors(&mut text, factors);
+        }
+        for (t, factors, (p1, p2)) in self.partials.iter() {
+            text.push_str(&format!("partial {} {} {}", t, p1, p2));
+            write_factors(&mut text, factors);
+        }
+        text
+    }
+
+
+    pub fn from_text(text: &str) -> Result<Checkpoint, String> {
+        let (mut n, mut method, mut max_large_primes, mut target) = (None, None, None, None);
+        let (mut factors, mut remaining, mut relations, mut partials) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("n") => n = Some(parse(words.next(), line)?),
+                Some("method") => method = Some(match words.next() {
+                    Some("random_squares") => Method::RandomSquares { chosen_primes_bound: parse(words.next(), line)? },
+                    Some("quadratic_sieve") => Method::QuadraticSieve { multiplier: parse(words.next(), line)?, base_size: parse(words.next(), line)? },
+                    _ => return Err(format!("Unknown method in '{}'", line)),
+                }),
+                Some("large_primes") => max_large_primes = Some(parse(words.next(), line)?),
+                Some("target") => target = Some(parse(words.next(), line)?),
+                Some("factor") => factors.push(parse(words.next(), line)?),
+                Some("remaining") => remaining.push(parse(words.next(), line)?),
+                Some("relation") => {
+                    let t = parse(words.next(), line)?;
+                    relations.push((t, parse_factors(words, line)?));
+                },
+                Some("partial") => {
+                    let t = parse(words.next(), line)?;
+                    let large = (parse(words.next(), line)?, parse(words.next(), line)?);
+                    partials.push((t, parse_factors(words, line)?, large));
+                },
+                _ => return Err(format!("Unknown line '{}'", line)),
+            }
+        }
+
+        Ok(Checkpoint {
+            n: n.ok_or("The checkpoint has no n")?,
+            method: method.ok_or("The checkpoint has no method")?,
+            max_large_primes: max_large_primes.ok_or("The checkpoint has no number of large primes")?,
+            target: target.ok_or("The checkpoint has no target")?,
+            factors,
+            remaining,
+            relations,
+            partials,
+        })
+    }
+
+
+    // Writes to a temporary file first and renames it, so that a job killed while writing still
+    // leaves the previous checkpoint.
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, self.to_text())?;
+        fs::rename(&temporary, path)
+    }
+
+
+    // For the checkpoints written along the way, where a failure should not stop the job.
+    pub(crate) fn write_or_report(&self, path: &Path) {
+        if let Err(error) = self.write(path) {
+            eprintln!("Could not write the checkpoint {}: {}", path.display(), error);
+        }
+    }
+
+
+    pub fn read(path: &Path) -> Result<Checkpoint, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+        Checkpoint::from_text(&text)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let n = Integer::from(1000003*1000033u64);
+        let mut job = Checkpoint::new(&n, Method::QuadraticSieve { multiplier: 3, base_size: 200 }, 2, 232);
+        job.factors.push(Integer::from(7));
+        job.relations.push((Integer::from(5), vec![(1, 1), (2, 3)]));
+        job.relations.push((Integer::from(6), vec![]));
+        job.partials.push((Integer::from(11), vec![(3, 1)], (101, 1)));
+        assert_eq!(Checkpoint::from_text(&job.to_text()), Ok(job.clone()));
+
+        let path = std::env::temp_dir().join(format!("checkpoint-test-{}", std::process::id()));
+        job.write(&path).unwrap();
+        assert_eq!(Checkpoint::read(&path), Ok(job.clone()));
+        fs::remove_file(&path).unwrap();
+        assert!(Checkpoint::read(&path).is_err());
+
+        let job = Checkpoint::new(&n, Method::RandomSquares { chosen_primes_bound: 499 }, 0, 100);
+        assert_eq!(Checkpoint::from_text(&job.to_text()), Ok(job.clone()));
+
+        assert!(Checkpoint::from_text("n 15\nmethod random_squares 499\nlarge_primes 2\n").is_err());
+        assert!(Checkpoint::from_text("n 15\nmethod random_squares 499\nlarge_primes 2\ntarget 10\nrelation 4 2^x\n").is_err());
+        assert!(Checkpoint::from_text("n 15\nmethod ecm\nlarge_primes 2\ntarget 10\n").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use rug::{Complete, Integer};
+use crate::factor::factor_base::{automatic_size, FactorBase, EXTRA_RELATIONS};
+use crate::factor::quadratic_sieve::{knuth_schroeppel, MULTIPLIERS};
+use crate::factor::random_squares::{factor_from_square, find_all_squares_by_relations};
+use crate::integers::prime::baillie_psw_is_prime;
+
+
+// Relations from the continued fraction expansion sqrt(kn) = [a_0; a_1, a_2, ...], given by
+//      a_i = floor((a_0 + P_i)/Q_i),    P_(i+1) = a_i Q_i - P_i,    Q_(i+1) = (kn - P_(i+1)^2)/Q_i
+// from P_0 = 0 and Q_0 = 1. The numerators A_i = a_i A_(i-1) + A_(i-2) of the convergents have
+// A_(i-1)^2 - kn B_(i-1)^2 = (-1)^i Q_i with 0 < Q_i < 2 sqrt(kn), so every Q_i which is smooth
+// gives the relation A_(i-1)^2 = (-1)^i Q_i (mod n). Stops early at the end of the period of the
+// expansion, after which the relations repeat, or returns a factor of n found in the factor base.
+pub fn find_relations_by_continued_fraction(n: &Integer, k: u64, count: usize) -> Result<HashMap<Integer, Vec<(u64, u64)>>, Integer> {
+    let base = FactorBase::with_automatic_size(n, k)?;
+    let kn = (n*k).complete();
+    let a0 = kn.sqrt_ref().complete();
+
+    let mut relations: HashMap<Integer, Vec<(u64, u64)>> = HashMap::with_capacity(count);
+    if a0.square_ref().complete() == kn {
+        return Ok(relations);
+    }
+
+    let (mut p, mut q) = (Integer::ZERO.clone(), Integer::ONE.clone());
+    let (mut a_previous, mut a_before_previous) = (Integer::ONE.clone(), Integer::ZERO.clone());
+    let mut i: usize = 0;
+    while relations.len() < count {
+        let a = (&a0 + &p).complete()/&q;
+        let numerator = (&a*&a_previous).complete() + &a_before_previous;
+        a_before_previous = std::mem::replace(&mut a_previous, numerator.modulo(n));
+        p = (&a*&q).complete() - &p;
+        q = (&kn - p.square_ref().complete())/&q;
+        i += 1;
+
+        let value = if i % 2 == 1 { (-&q).complete() } else { q.clone() };
+        if let Some(factors) = base.trial_divide(&value) {
+            relations.insert(a_previous.clone(), factors);
+        }
+        if q == 1 {
+            break;
+        }
+    }
+    Ok(relations)
+}
+
+
+// A proper factor of the odd composite n by the method of Morrison and Brillhart, with the
+// relations from the continued fraction of sqrt(kn) put through the same linear algebra as those
+// of random squares. If the period of the expansion ends before there are enough relations, the
+// next multiplier is used. Returns None if n is prime or every dependency gives a trivial factor.
+pub fn find_factor_by_continued_fraction(n: &Integer) -> Option<Integer> {
+    if *n < 4 || baillie_psw_is_prime(n) {
+        return None;
+    }
+    if n.is_even() {
+        return Some(Integer::from(2));
+    }
+
+    let best = knuth_schroeppel(n);
+    let count = automatic_size(n) + EXTRA_RELATIONS;
+    let mut relations: HashMap<Integer, Vec<(u64, u64)>> = HashMap::with_capacity(count);
+    for k in [best].into_iter().chain(MULTIPLIERS.into_iter().filter(|k| *k != best)) {
+        match find_relations_by_continued_fraction(n, k, count - relations.len()) {
+            Ok(found) => relations.extend(found),
+            Err(factor) => return Some(factor),
+        }
+        if relations.len() >= count {
+            break;
+        }
+    }
+
+    find_all_squares_by_relations(&relations)
+        .iter()
+        .map(|square| factor_from_square(n, &relations, square))
+        .find(|factor| *factor != 1 && factor != n)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::factor::random_squares::MINUS_ONE;
+    use crate::factor::test_helpers::check_relation;
+    use crate::integers::prime::find_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_relations_by_continued_fraction() {
+        let p = find_prime_with_bit_length(40, 30);
+        let q = find_prime_with_bit_length(40, 30);
+        let n = (&p*&q).complete();
+        let relations = find_relations_by_continued_fraction(&n, 1, 50).unwrap();
+        assert!(relations.len() >= 50);
+        for (t, factors) in relations.iter() {
+            check_relation(&n, t, factors);
+        }
+
+        // sqrt(13) = [3; 1, 1, 1, 1, 6] has period 5, and 3^2 = -4, 4^2 = 3, 7^2 = -3, 11^2 = 4
+        // and 18^2 = 5^2 = -1 modulo 13.
+        let relations = find_relations_by_continued_fraction(&Integer::from(13), 1, 100).unwrap();
+        assert_eq!(relations.len(), 5);
+        assert_eq!(relations[&Integer::from(7)], vec![(MINUS_ONE, 1), (3, 1)]);
+        assert_eq!(relations[&Integer::from(5)], vec![(MINUS_ONE, 1)]);
+    }
+
+
+    #[test]
+    fn test_find_factor_by_continued_fraction() {
+        let loops = 3;
+        for bits in [30, 40, 50] {
+            for _ in 0..loops {
+                let p = find_prime_with_bit_length(bits, 30);
+                let q = find_prime_with_bit_length(bits + 3, 30);
+                let n = (&p*&q).complete();
+                let factor = find_factor_by_continued_fraction(&n).unwrap();
+                assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, n);
+            }
+        }
+        assert_eq!(find_factor_by_continued_fraction(&find_prime_with_bit_length(100, 30)), None);
+    }
+}
@@ -0,0 +1,283 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use rayon::prelude::*;
+use rug::{Complete, Integer};
+use crate::factor::pollard_p_minus_one::{check_gcd, largest_power_below, SmoothnessResult};
+use crate::integers::sieve::{primes_in_range, PrimeTable};
+use crate::random::randint_below;
+
+
+// Stage 2 writes each prime q = wj +- i with 0 < i < w/2 and gcd(i, w) = 1, so only the
+// baby steps iP with such i are needed. w = 2*3*5*7*11.
+const GIANT_STEP: u64 = 2310;
+
+// (digits, B1, expected number of curves) to find a factor with that many digits, from the
+// tables of GMP-ECM. B2 is taken as 100*B1.
+const ECM_PARAMETERS: [(usize, u64, usize); 7] = [
+    (15, 2000, 25),
+    (20, 11000, 90),
+    (25, 50000, 300),
+    (30, 250000, 700),
+    (35, 1000000, 1800),
+    (40, 3000000, 5100),
+    (45, 11000000, 10600),
+];
+
+
+// A point (X : Z) on the Montgomery curve By^2 = x^3 + Ax^2 + x. Only x = X/Z is kept, which
+// determines the point up to sign, and is enough to compute multiples.
+#[derive(Debug, Clone)]
+struct Point {
+    x: Integer,
+    z: Integer,
+}
+
+
+// The curve is only needed through a24 = (A + 2)/4.
+#[derive(Debug, Clone)]
+struct MontgomeryCurve {
+    n: Integer,
+    a24: Integer,
+}
+
+
+// The inverse of a modulo n, or the gcd which made it fail.
+fn invert(a: &Integer, n: &Integer) -> Result<Integer, SmoothnessResult> {
+    match a.invert_ref(n) {
+        Some(inverse) => Ok(Integer::from(inverse)),
+        None => Err(check_gcd(a, n).unwrap_or(SmoothnessResult::AllFactorsAtOnce)),
+    }
+}
+
+
+impl MontgomeryCurve {
+    // Suyama's parametrisation: with u = sigma^2 - 5 and v = 4 sigma the curve
+    //      A = (v - u)^3 (3u + v)/(4u^3 v) - 2,    P = (u^3 : v^3)
+    // has a group order divisible by 12, which makes it more likely to be smooth.
+    fn suyama(n: &Integer, sigma: &Integer) -> Result<(MontgomeryCurve, Point), SmoothnessResult> {
+        let u: Integer = (sigma.square_ref().complete() - 5u32).modulo(n);
+        let v: Integer = (sigma*4u32).complete().modulo(n);
+        let x = u.clone().pow_mod(&Integer::from(3), n).expect("The exponent is positive");
+        let z = v.clone().pow_mod(&Integer::from(3), n).expect("The exponent is positive");
+
+        let numerator: Integer = ((&v - &u).complete().pow_mod(&Integer::from(3), n).expect("The exponent is positive")
+            * (3u32*&u + &v).complete()).modulo(n);
+        let denominator: Integer = (16u32*(&x*&v).complete()).modulo(n);
+        let a24 = (numerator*invert(&denominator, n)?).modulo(n);
+
+        Ok((MontgomeryCurve { n: n.clone(), a24 }, Point { x, z }))
+    }
+
+
+    // 2P, from (X + Z)^2 - (X - Z)^2 = 4XZ.
+    fn double(&self, p: &Point) -> Point {
+        let sum = (&p.x + &p.z).complete().square() % &self.n;
+        let difference = (&p.x - &p.z).complete().square() % &self.n;
+        let four_xz = (&sum - &difference).complete();
+        let x = (&sum*&difference).complete() % &self.n;
+        let z = (&four_xz*(difference + (&self.a24*&four_xz).complete())) % &self.n;
+        Point { x, z }
+    }
+
+
+    // P + Q from P, Q and P - Q.
+    fn add(&self, p: &Point, q: &Point, difference: &Point) -> Point {
+        let u = ((&p.x - &p.z).complete()*(&q.x + &q.z).complete()) % &self.n;
+        let v = ((&p.x + &p.z).complete()*(&q.x - &q.z).complete()) % &self.n;
+        let x = (&difference.z*(&u + &v).complete().square()) % &self.n;
+        let z = (&difference.x*(u - v).square()) % &self.n;
+        Point { x, z }
+    }
+
+
+    // kP by Montgomery's ladder, keeping (mP, (m+1)P) for the leading bits m of k.
+    fn multiply(&self, p: &Point, k: &Integer) -> Point {
+        if *k == 0 {
+            return Point { x: Integer::ONE.clone(), z: Integer::ZERO.clone() };
+        }
+        let mut low = p.clone();
+        let mut high = self.double(p);
+        for i in (0..k.significant_bits() - 1).rev() {
+            if k.get_bit(i) {
+                low = self.add(&high, &low, p);
+                high = self.double(&high);
+            } else {
+                high = self.add(&high, &low, p);
+                low = self.double(&low);
+            }
+        }
+        low
+    }
+}
+
+
+// One curve with Suyama parameter sigma. In stage 1 P is multiplied by every prime power below
+// B1, and p | gcd(Z, n) when the order of P modulo p divides their product. Stage 2 finds p when
+// one more prime q in (B1, B2] is needed, as then (wj)P = +-iP modulo p for q = wj +- i, and p
+// divides X_(wj) - x_i Z_(wj) with the baby steps normalised to x_i = X_i/Z_i. Only the primes of
+// stage 1 are kept in the prime table, those of stage 2 are sieved a segment at a time, as B2 is
+// too large to keep all of them.
+pub fn ecm_with_sigma(n: &Integer, sigma: &Integer, b1: u64, b2: u64) -> SmoothnessResult {
+    let primes = PrimeTable::up_to(b1.max(GIANT_STEP/2));
+    ecm_with_primes(n, sigma, b1, b2, primes.as_slice())
+}
+
+
+fn ecm_with_primes(n: &Integer, sigma: &Integer, b1: u64, b2: u64, primes: &[u64]) -> SmoothnessResult {
+    if n.is_even() {
+        return SmoothnessResult::Factor(Integer::from(2));
+    }
+    let (curve, mut p) = match MontgomeryCurve::suyama(n, sigma) {
+        Ok(curve_and_point) => curve_and_point,
+        Err(result) => return result,
+    };
+
+    // Primes below w/2 can't be written as wj +- i with gcd(i, w) = 1, so they belong to stage 1.
+    let b1 = b1.max(GIANT_STEP/2);
+    let end_of_stage_one = primes.partition_point(|q| *q <= b1);
+    for q in primes[..end_of_stage_one].iter() {
+        p = curve.multiply(&p, &largest_power_below(*q, b1));
+    }
+    if let Some(result) = check_gcd(&p.z, n) {
+        return result;
+    }
+
+    let mut stage_two = primes_in_range(b1 + 1, b2.saturating_add(1)).peekable();
+    let Some(&first_prime) = stage_two.peek() else {
+        return SmoothnessResult::BoundsExhausted;
+    };
+
+    // Baby steps iP for odd i < w/2, from (i + 2)P = iP + 2P with difference (i - 2)P.
+    let half = (GIANT_STEP/2) as usize;
+    let double = curve.double(&p);
+    let mut baby: Vec<Point> = vec![p.clone(), curve.add(&double, &p, &p)];
+    while 2*baby.len() + 1 < half {
+        let next = curve.add(&baby[baby.len() - 1], &double, &baby[baby.len() - 2]);
+        baby.push(next);
+    }
+    let coprime: Vec<usize> = (0..baby.len()).filter(|k| [3, 5, 7, 11].iter().all(|r| (2*k + 1) % r != 0)).collect();
+
+    // x_i = X_i/Z_i with a single inversion: invert the product of all Z_i and peel off one at a
+    // time. A failed inversion means some Z_i shares a factor with n.
+    let mut prefix: Vec<Integer> = Vec::with_capacity(coprime.len());
+    let mut product = Integer::ONE.clone();
+    for k in coprime.iter() {
+        prefix.push(product.clone());
+        product = (product*&baby[*k].z).modulo(n);
+    }
+    let mut inverse = match invert(&product, n) {
+        Ok(inverse) => inverse,
+        Err(result) => return result,
+    };
+    let mut normalised: Vec<Integer> = vec![Integer::ZERO.clone(); baby.len()];
+    for (k, before) in coprime.iter().zip(prefix).rev() {
+        let z_inverse = (&inverse*&before).complete().modulo(n);
+        normalised[*k] = (&baby[*k].x*&z_inverse).complete().modulo(n);
+        inverse = (inverse*&baby[*k].z).modulo(n);
+    }
+
+    // Giant steps (wj)P from (w(j+1))P = (wj)P + wP with difference (w(j-1))P, except for j = 1
+    // where the difference would be the point at infinity.
+    let first_j = (first_prime + GIANT_STEP/2)/GIANT_STEP;
+    let step = curve.multiply(&p, &Integer::from(GIANT_STEP));
+    let mut previous = curve.multiply(&p, &Integer::from((first_j - 1)*GIANT_STEP));
+    let mut giant = curve.multiply(&p, &Integer::from(first_j*GIANT_STEP));
+    let mut j = first_j;
+
+    let mut accumulated = Integer::ONE.clone();
+    for (index, q) in stage_two.enumerate() {
+        while j*GIANT_STEP + GIANT_STEP/2 < q {
+            let next = if j == 1 { curve.double(&giant) } else { curve.add(&giant, &step, &previous) };
+            previous = std::mem::replace(&mut giant, next);
+            j += 1;
+        }
+        let i = (j*GIANT_STEP).abs_diff(q) as usize;
+        let term = (&giant.x - (&normalised[(i - 1)/2]*&giant.z).complete()) % n;
+        accumulated = (accumulated*term) % n;
+        if index % 1000 == 999 {
+            if let Some(result) = check_gcd(&accumulated, n) {
+                return result;
+            }
+        }
+    }
+
+    check_gcd(&accumulated, n).unwrap_or(SmoothnessResult::BoundsExhausted)
+}
+
+
+// Runs `curves` curves with random sigma in parallel and returns the first factor found. A curve
+// finding all factors at once is only reported if no other curve separates them.
+pub fn ecm(n: &Integer, b1: u64, b2: u64, curves: usize) -> SmoothnessResult {
+    if n.is_even() {
+        return SmoothnessResult::Factor(Integer::from(2));
+    }
+    let primes = PrimeTable::up_to(b1.max(GIANT_STEP/2));
+    let sigma_bound = (n - 6u32).complete().max(Integer::ONE.clone());
+    let all_factors_at_once = AtomicBool::new(false);
+
+    let factor = (0..curves)
+        .into_par_iter()
+        .find_map_any(|_| {
+            let sigma = randint_below(&sigma_bound) + 6u32;
+            match ecm_with_primes(n, &sigma, b1, b2, primes.as_slice()) {
+                SmoothnessResult::Factor(factor) => Some(factor),
+                SmoothnessResult::AllFactorsAtOnce => {
+                    all_factors_at_once.store(true, Ordering::Relaxed);
+                    None
+                },
+                SmoothnessResult::BoundsExhausted => None,
+            }
+        });
+
+    match factor {
+        Some(factor) => SmoothnessResult::Factor(factor),
+        None if all_factors_at_once.load(Ordering::Relaxed) => SmoothnessResult::AllFactorsAtOnce,
+        None => SmoothnessResult::BoundsExhausted,
+    }
+}
+
+
+// Goes through the parameters for factors of 15, 20, ... digits until a factor is found. Returns
+// None if there is no factor below 10^max_digits, at least with high probability.
+pub fn find_factor_by_ecm(n: &Integer, max_digits: usize) -> Option<Integer> {
+    for (digits, b1, curves) in ECM_PARAMETERS.iter() {
+        if *digits > max_digits {
+            break;
+        }
+        if let SmoothnessResult::Factor(factor) = ecm(n, *b1, 100*b1, *curves) {
+            return Some(factor);
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::integers::prime::find_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_ecm() {
+        let loops = 3;
+        for _ in 0..loops {
+            let p = find_prime_with_bit_length(40, 30);
+            let q = find_prime_with_bit_length(200, 30);
+            let n = (&p*&q).complete();
+            assert_eq!(ecm(&n, 2000, 200000, 100), SmoothnessResult::Factor(p.clone()));
+            assert_eq!(find_factor_by_ecm(&n, 20), Some(p));
+        }
+        assert_eq!(ecm(&Integer::from(1000), 10, 10, 1), SmoothnessResult::Factor(Integer::from(2)));
+    }
+
+
+    #[test]
+    fn test_failed_inversion_gives_factor() {
+        // With sigma = p we get v = 4p, so the point (u^3 : v^3) has Z = 0 modulo p, and the
+        // inversion in Suyama's parametrisation fails.
+        let p = find_prime_with_bit_length(64, 30);
+        let q = find_prime_with_bit_length(64, 30);
+        let n = (&p*&q).complete();
+        assert_eq!(ecm_with_sigma(&n, &p, 10, 10), SmoothnessResult::Factor(p));
+    }
+}
@@ -0,0 +1,176 @@
+use rug::{Complete, Integer};
+use crate::factor::random_squares::MINUS_ONE;
+use crate::integers::integer_computations::sqrt_mod_u64;
+use crate::integers::sieve::PrimeTable;
+
+
+// L(n)^SIZE_EXPONENT primes are used, see automatic_size.
+const SIZE_EXPONENT: f64 = 0.31;
+
+// Relations beyond the size of the factor base, each giving about one more dependency.
+pub const EXTRA_RELATIONS: usize = 32;
+
+
+// A prime of the factor base with a square root of kn modulo p and log_2 p rounded, for sieving.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactorBasePrime {
+    pub p: u64,
+    pub root: u64,
+    pub log: u8,
+}
+
+
+// The primes which can divide t^2 - kn for the methods taking t close to sqrt(kn), which are 2,
+// the primes dividing k, and the odd p with (kn/p) = 1, since p | t^2 - kn means that kn is a
+// square modulo p. The sign of t^2 - kn is the factor -1, which is kept as MINUS_ONE. The other
+// half of the primes never divides t^2 - kn, and would only cost trial divisions and columns in
+// the matrix.
+#[derive(Debug, Clone)]
+pub struct FactorBase {
+    multiplier: u64,
+    primes: Vec<FactorBasePrime>,
+}
+
+
+// The size of the factor base for n from L(n) = exp(sqrt(ln n ln ln n)). The analysis of the
+// quadratic sieve asks for the primes up to L(n)^(1/2), but far fewer are faster in practice, and
+// L(n)^0.31 primes is close to the tables of Contini from 20 to 80 digits.
+pub fn automatic_size(n: &Integer) -> usize {
+    let ln_n = n.significant_bits() as f64*2f64.ln();
+    let l = (ln_n*ln_n.ln()).sqrt().exp();
+    (l.powf(SIZE_EXPONENT) as usize).max(20)
+}
+
+
+impl FactorBase {
+    // The first `size` primes for kn, or a factor of n if one is found among the primes.
+    pub fn new(n: &Integer, k: u64, size: usize) -> Result<FactorBase, Integer> {
+        let kn = (n*k).complete();
+        let mut primes = Vec::with_capacity(size);
+        let mut bound = 16*size as u64 + 100;
+        while primes.len() < size {
+            primes.clear();
+            for p in PrimeTable::up_to(bound).iter() {
+                if n.is_divisible_u(p as u32) && *n != p {
+                    return Err(Integer::from(p));
+                }
+                if let Some(root) = sqrt_mod_u64(kn.mod_u(p as u32) as u64, p) {
+                    primes.push(FactorBasePrime { p, root, log: (p as f64).log2().round() as u8 });
+                    if primes.len() == size {
+                        break;
+                    }
+                }
+            }
+            bound *= 2;
+        }
+        Ok(FactorBase { multiplier: k, primes })
+    }
+
+
+    pub fn with_automatic_size(n: &Integer, k: u64) -> Result<FactorBase, Integer> {
+        FactorBase::new(n, k, automatic_size(n))
+    }
+
+
+    pub fn multiplier(&self) -> u64 {
+        self.multiplier
+    }
+
+
+    pub fn as_slice(&self) -> &[FactorBasePrime] {
+        &self.primes
+    }
+
+
+    pub fn iter(&self) -> std::slice::Iter<'_, FactorBasePrime> {
+        self.primes.iter()
+    }
+
+
+    // The number of primes, not counting -1.
+    pub fn len(&self) -> usize {
+        self.primes.len()
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.primes.is_empty()
+    }
+
+
+    pub fn largest(&self) -> Option<u64> {
+        self.primes.last().map(|prime| prime.p)
+    }
+
+
+    // The factorization of the value over -1 and the primes, if it is smooth.
+    pub fn trial_divide(&self, value: &Integer) -> Option<Vec<(u64, u64)>> {
+        let mut factors: Vec<(u64, u64)> = Vec::new();
+        if *value < 0 {
+            factors.push((MINUS_ONE, 1));
+        }
+        let mut value = value.clone().abs();
+        for prime in self.primes.iter() {
+            let mut exponent = 0;
+            while value.is_divisible_u(prime.p as u32) {
+                value /= prime.p as u32;
+                exponent += 1;
+            }
+            if exponent > 0 {
+                factors.push((prime.p, exponent));
+            }
+        }
+        if value == 1 {
+            Some(factors)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::integers::prime::find_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_factor_base() {
+        let p = find_prime_with_bit_length(60, 30);
+        let q = find_prime_with_bit_length(60, 30);
+        let n = (&p*&q).complete();
+        for k in [1u64, 3, 7] {
+            let kn = (&n*k).complete();
+            let base = FactorBase::new(&n, k, 200).unwrap();
+            assert_eq!(base.len(), 200);
+            assert_eq!(base.multiplier(), k);
+            for prime in base.iter() {
+                assert_eq!((prime.root*prime.root) % prime.p, kn.mod_u(prime.p as u32) as u64 % prime.p);
+                assert!(prime.p == 2 || kn.jacobi(&Integer::from(prime.p)) != -1, "{} is not a square modulo {}", kn, prime.p);
+            }
+            // Only about half of the odd primes are kept.
+            let largest = base.largest().unwrap();
+            let primes_below = PrimeTable::up_to(largest).len();
+            assert!(primes_below > 300 && primes_below < 500, "{} primes up to {}", primes_below, largest);
+        }
+
+        let n = Integer::from(1009)*&p;
+        assert_eq!(FactorBase::new(&n, 1, 500).unwrap_err(), 1009);
+
+        let small = automatic_size(&Integer::u_pow_u(10, 30).complete());
+        let large = automatic_size(&Integer::u_pow_u(10, 60).complete());
+        assert!(small >= 20 && small < large);
+    }
+
+
+    #[test]
+    fn test_trial_divide() {
+        // 1 is a square modulo every prime, so this is the first 10 primes.
+        let base = FactorBase::new(&Integer::ONE.clone(), 1, 10).unwrap();
+        assert_eq!(base.trial_divide(&Integer::from(-360)), Some(vec![(MINUS_ONE, 1), (2, 3), (3, 2), (5, 1)]));
+        assert_eq!(base.trial_divide(&Integer::from(23*29)), Some(vec![(23, 1), (29, 1)]));
+        assert_eq!(base.trial_divide(&Integer::from(31)), None);
+        assert_eq!(base.trial_divide(&Integer::from(-1)), Some(vec![(MINUS_ONE, 1)]));
+    }
+}
@@ -0,0 +1,127 @@
+use rug::{Complete, Integer};
+use crate::factor::ecm::find_factor_by_ecm;
+use crate::factor::fermat::{fermat_factor, hart_one_line_factor};
+use crate::factor::file_handler::number_of_chosen_primes;
+use crate::factor::pollard_rho::find_factor_by_pollard_rho;
+use crate::factor::quadratic_sieve::find_factor_by_quadratic_sieve;
+use crate::factor::random_squares::find_two_real_factors_by_random_squares;
+use crate::factor::squfof::{squfof, SQUFOF_BOUND};
+use crate::integers::prime::baillie_psw_is_prime;
+use crate::integers::sieve::PrimeTable;
+
+
+// Factors found by trial division are only searched for below this bound, the remaining cofactor
+// is split by the general methods.
+const TRIAL_DIVISION_BOUND: u64 = 1000;
+
+// Enough steps of Pollard's rho to find factors up to about 10^12, larger ones are left to the
+// methods whose running time depends on n instead.
+pub const POLLARD_RHO_ITERATIONS: usize = 1 << 22;
+
+// ECM looks for factors up to this many digits, but never for more than a third of the digits of
+// the cofactor, as the quadratic sieve is faster for anything close to a balanced product.
+pub const ECM_MAX_DIGITS: usize = 30;
+
+// Below this Hart's one line factoring is tried before SQUFOF, with at most HART_ITERATIONS steps,
+// which is about n^(1/3) at the bound.
+const HART_BOUND: u64 = 1 << 42;
+const HART_ITERATIONS: usize = 1 << 16;
+
+// Fermat's method is tried briefly on every cofactor too large for the native methods, which is
+// enough to split p*q where p and q agree in their leading half.
+pub const FERMAT_ITERATIONS: usize = 1 << 10;
+
+
+// Returns the prime factorization of |n| as pairs (p, e) sorted by p.
+pub fn factorize(n: &Integer) -> Vec<(Integer, u32)> {
+    if n.is_zero() {
+        panic!("Zero has no prime factorization");
+    }
+
+    let mut factors: Vec<(Integer, u32)> = Vec::new();
+    let mut m: Integer = n.clone().abs();
+
+    for p in PrimeTable::up_to(TRIAL_DIVISION_BOUND).iter() {
+        if (p*p) > m {
+            break;
+        }
+        let mut exponent = 0;
+        while m.is_divisible_u(p as u32) {
+            m /= p;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((Integer::from(p), exponent));
+        }
+    }
+
+    if m != 1 {
+        split_cofactor(&m, 1, &mut factors);
+    }
+
+    merge_factorization(factors)
+}
+
+
+// Splits m, which has no factors below TRIAL_DIVISION_BOUND, into primes and pushes them with
+// their exponent multiplied by `multiplicity`.
+fn split_cofactor(m: &Integer, multiplicity: u32, factors: &mut Vec<(Integer, u32)>) {
+    if baillie_psw_is_prime(m) {
+        factors.push((m.clone(), multiplicity));
+        return;
+    }
+
+    if m.is_perfect_power() {
+        for k in (2..=m.significant_bits()).rev() {
+            let (root, rem) = m.root_rem_ref(k).complete();
+            if rem.is_zero() {
+                split_cofactor(&root, multiplicity*k, factors);
+                return;
+            }
+        }
+    }
+
+    let (factor1, factor2) = match find_factor(m) {
+        Some(factor) => {
+            let cofactor = (m / &factor).complete();
+            (factor, cofactor)
+        },
+        None => find_two_real_factors_by_random_squares(m, number_of_chosen_primes() + 10),
+    };
+    split_cofactor(&factor1, multiplicity, factors);
+    split_cofactor(&factor2, multiplicity, factors);
+}
+
+
+// A proper factor of the composite m, chosen by size: the native methods while m fits in a u64,
+// and otherwise Fermat for close factors, then rho, ECM and the quadratic sieve for factors of
+// increasing size.
+fn find_factor(m: &Integer) -> Option<Integer> {
+    if let Some(small) = m.to_u64().filter(|small| *small < SQUFOF_BOUND) {
+        let factor = if small < HART_BOUND {
+            hart_one_line_factor(small, HART_ITERATIONS).or_else(|| squfof(small))
+        } else {
+            squfof(small)
+        };
+        return factor.map(Integer::from).or_else(|| find_factor_by_pollard_rho(m, POLLARD_RHO_ITERATIONS));
+    }
+
+    let ecm_digits = ECM_MAX_DIGITS.min(m.to_string().len()/3);
+    fermat_factor(m, FERMAT_ITERATIONS)
+        .or_else(|| find_factor_by_pollard_rho(m, POLLARD_RHO_ITERATIONS))
+        .or_else(|| find_factor_by_ecm(m, ecm_digits))
+        .or_else(|| find_factor_by_quadratic_sieve(m))
+}
+
+
+fn merge_factorization(mut factors: Vec<(Integer, u32)>) -> Vec<(Integer, u32)> {
+    factors.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut merged: Vec<(Integer, u32)> = Vec::with_capacity(factors.len());
+    for (p, e) in factors {
+        match merged.last_mut() {
+            Some(last) if last.0 == p => last.1 += e,
+            _ => merged.push((p, e)),
+        }
+    }
+    merged
+}
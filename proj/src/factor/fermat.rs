@@ -0,0 +1,126 @@
+use rug::{Complete, Integer};
+use crate::integers::integer_computations::mul_mod_u64;
+
+
+// Fermat's method writes the odd n = pq as a^2 - b^2 with a = (p + q)/2 and b = (q - p)/2, trying
+// a = ceil(sqrt(n)), ceil(sqrt(n)) + 1, ... until a^2 - n is a square. It takes about
+// (q - p)^2/(8 sqrt(n)) steps, so it finds factors which are close at once, as in an RSA modulus
+// with |p - q| small, and is hopeless otherwise. Returns None after max_iterations steps.
+pub fn fermat_factor(n: &Integer, max_iterations: usize) -> Option<Integer> {
+    if n.is_even() {
+        return if *n > 2 { Some(Integer::from(2)) } else { None };
+    }
+    let (mut a, remainder) = n.sqrt_rem_ref().complete();
+    if remainder != 0 {
+        a += 1;
+    }
+    let mut difference = (a.square_ref() - n).complete();
+
+    for _ in 0..max_iterations {
+        if difference.is_perfect_square() {
+            let factor = a - difference.sqrt();
+            return if factor == 1 { None } else { Some(factor) };
+        }
+        // (a + 1)^2 - n = a^2 - n + 2a + 1
+        difference += &a;
+        difference += &a;
+        difference += 1;
+        a += 1;
+    }
+    None
+}
+
+
+// Whether n is a square, as the integer square root of n if it is.
+fn square_root(n: u128) -> Option<u128> {
+    let root = n.isqrt();
+    if root*root == n {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+
+// Hart's one line factoring: for i = 1, 2, ... take s = ceil(sqrt(in)) and check whether
+// s^2 mod n = t^2 is a square, in which case gcd(s - t, n) is usually a proper factor. Since
+// s^2 - in is small, it is a square far more often than a random residue, and the method needs
+// O(n^(1/3)) steps. Multiplying n by 480 first makes s^2 - in divisible by many small primes.
+pub fn hart_one_line_factor(n: u64, max_iterations: usize) -> Option<u64> {
+    if n < 4 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    if let Some(root) = square_root(n as u128) {
+        return Some(root as u64);
+    }
+
+    let multiplier = 480*n as u128;
+    for i in 1..=max_iterations as u128 {
+        let product = multiplier*i;
+        let mut s = product.isqrt();
+        if s*s != product {
+            s += 1;
+        }
+        let s_mod_n = (s % n as u128) as u64;
+        let m = mul_mod_u64(s_mod_n, s_mod_n, n);
+        if let Some(t) = square_root(m as u128) {
+            let factor = gcd(n, s_mod_n.abs_diff(t as u64));
+            if factor != 1 && factor != n {
+                return Some(factor);
+            }
+        }
+    }
+    None
+}
+
+
+pub(crate) fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::integers::prime::find_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_fermat_factor() {
+        let loops = 5;
+        for _ in 0..loops {
+            // An RSA modulus where p and q share their leading half.
+            let p = find_prime_with_bit_length(512, 30);
+            let q = (&p + (Integer::from(1) << 200u32)).next_prime();
+            let n = (&p*&q).complete();
+            assert_eq!(fermat_factor(&n, 10), Some(p));
+        }
+
+        // 5959 = 59*101 = 80^2 - 21^2
+        assert_eq!(fermat_factor(&Integer::from(5959), 10), Some(Integer::from(59)));
+        assert_eq!(fermat_factor(&Integer::from(5959), 1), None);
+        assert_eq!(fermat_factor(&Integer::from(1000003), 1000000), None);
+    }
+
+
+    #[test]
+    fn test_hart_one_line_factor() {
+        let loops = 20;
+        for bits in [20, 30, 40] {
+            for _ in 0..loops {
+                let p = find_prime_with_bit_length(bits/2, 30).to_u64().unwrap();
+                let q = find_prime_with_bit_length(bits/2 + 2, 30).to_u64().unwrap();
+                let factor = hart_one_line_factor(p*q, 1 << 20).unwrap();
+                assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, p*q);
+            }
+        }
+        assert_eq!(hart_one_line_factor(1000003*1000003, 10), Some(1000003));
+        assert_eq!(hart_one_line_factor(1000003, 1000), None);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{HashMap, VecDeque};
+use rug::Integer;
+use crate::factor::random_squares::merge_tuples;
+use crate::factor::squfof::squfof;
+use crate::integers::prime::baillie_psw_is_prime;
+
+
+// Partial relations may have cofactors up to LARGE_PRIME_MULTIPLIER times the largest prime of the
+// factor base, and up to the square of that with two large primes.
+pub const LARGE_PRIME_MULTIPLIER: u64 = 64;
+
+// The number of large primes allowed in a partial relation by default, which is the double large
+// prime variation.
+pub const LARGE_PRIMES: usize = 2;
+
+// The cofactors given to squfof are below bound*(bound + 1), which this keeps below SQUFOF_BOUND.
+const MAX_LARGE_PRIME_BOUND: u64 = 1 << 30;
+
+
+pub fn large_prime_bound(largest: u64) -> u64 {
+    largest.saturating_mul(LARGE_PRIME_MULTIPLIER).min(MAX_LARGE_PRIME_BOUND)
+}
+
+
+// The large primes of the cofactor left by trial division with the primes up to `largest`, if it
+// is a product of at most `max_large_primes` primes below `bound`. A full relation gives (1, 1)
+// and one large prime P gives (P, 1). As the cofactor has no prime factor up to `largest`, it is
+// prime if it is below largest^2.
+pub fn large_primes(cofactor: &Integer, largest: u64, bound: u64, max_large_primes: usize) -> Option<(u64, u64)> {
+    let c = cofactor.to_u64()?;
+    if c == 1 {
+        return Some((1, 1));
+    }
+    if max_large_primes == 0 {
+        return None;
+    }
+    let is_prime = |m: u64| m/largest < largest || baillie_psw_is_prime(&Integer::from(m));
+    if is_prime(c) {
+        return (c <= bound).then_some((c, 1));
+    }
+    if max_large_primes == 1 || c/bound > bound {
+        return None;
+    }
+    let p = squfof(c)?;
+    let (p, q) = (p.min(c/p), p.max(c/p));
+    (q <= bound && is_prime(p) && is_prime(q)).then_some((p, q))
+}
+
+
+// A partial relation t^2 = P_1 P_2 prod p^e (mod n) with P_2 = 1 for a single large prime.
+#[derive(Debug, Clone)]
+struct PartialRelation {
+    t: Integer,
+    factors: Vec<(u64, u64)>,
+    large: (u64, u64),
+}
+
+
+// The partial relations as the edges of a graph on the large primes, with the vertex 1 for the
+// missing second prime of single large prime relations. A cycle has every large prime on two of
+// its edges, so multiplying its relations gives T^2 = prod P_j^2 prod p^e (mod n) with T the
+// product of the t, which is a full relation with the P_j taken as primes of exponent 2. Only a
+// spanning forest is kept, and a relation joining two vertices of the same tree closes the cycle
+// through the path between them.
+#[derive(Debug, Clone, Default)]
+pub struct PartialRelations {
+    relations: Vec<PartialRelation>,
+    // Union-find on the vertices, for telling if two of them are in the same tree.
+    parent: HashMap<u64, u64>,
+    // The edges of the forest at each vertex, as (other vertex, index of the relation).
+    edges: HashMap<u64, Vec<(u64, usize)>>,
+}
+
+
+impl PartialRelations {
+    pub fn new() -> PartialRelations {
+        PartialRelations::default()
+    }
+
+
+    // The number of partial relations in the forest.
+    pub fn len(&self) -> usize {
+        self.relations.len()
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.relations.is_empty()
+    }
+
+
+    // The partial relations as (t, factors, large primes), in the order they were added. Adding
+    // them again in any order gives the same trees, as they never close a cycle.
+    pub fn iter(&self) -> impl Iterator<Item = (&Integer, &[(u64, u64)], (u64, u64))> {
+        self.relations.iter().map(|relation| (&relation.t, relation.factors.as_slice(), relation.large))
+    }
+
+
+    fn root(&mut self, vertex: u64) -> u64 {
+        let mut root = vertex;
+        while let Some(parent) = self.parent.get(&root).filter(|parent| **parent != root) {
+            root = *parent;
+        }
+        // Path compression
+        let mut current = vertex;
+        while current != root {
+            let next = self.parent[&current];
+            self.parent.insert(current, root);
+            current = next;
+        }
+        root
+    }
+
+
+    // The indices of the relations on the path from u to v in the forest.
+    fn path(&self, u: u64, v: u64) -> Vec<usize> {
+        let mut previous: HashMap<u64, (u64, usize)> = HashMap::new();
+        let mut queue = VecDeque::from([u]);
+        while let Some(vertex) = queue.pop_front() {
+            if vertex == v {
+                break;
+            }
+            for (next, index) in self.edges.get(&vertex).into_iter().flatten() {
+                if *next != u && !previous.contains_key(next) {
+                    previous.insert(*next, (vertex, *index));
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut vertex = v;
+        while vertex != u {
+            let (before, index) = previous[&vertex];
+            path.push(index);
+            vertex = before;
+        }
+        path
+    }
+
+
+    // Adds the partial relation t^2 = P_1 P_2 prod p^e (mod n) with large = (P_1, P_2), where
+    // P_2 = 1 for a single large prime, and returns the full relation it gives if it closes a
+    // cycle. A cofactor P^2 is a loop at P, which is a cycle by itself.
+    pub fn add(&mut self, n: &Integer, t: Integer, factors: Vec<(u64, u64)>, large: (u64, u64)) -> Option<(Integer, Vec<(u64, u64)>)> {
+        let (u, v) = large;
+        self.parent.entry(u).or_insert(u);
+        self.parent.entry(v).or_insert(v);
+        let (root_u, root_v) = (self.root(u), self.root(v));
+        if root_u != root_v {
+            self.parent.insert(root_u, root_v);
+            let index = self.relations.len();
+            self.edges.entry(u).or_default().push((v, index));
+            self.edges.entry(v).or_default().push((u, index));
+            self.relations.push(PartialRelation { t, factors, large });
+            return None;
+        }
+
+        let path = self.path(u, v);
+        // The same t found twice only gives a relation with every exponent even, which is trivial.
+        if path.iter().any(|index| self.relations[*index].t == t) {
+            return None;
+        }
+        let mut product = t;
+        let mut all_factors = factors;
+        let mut large_primes = vec![large.0, large.1];
+        for index in path {
+            let relation = &self.relations[index];
+            product = (product*&relation.t).modulo(n);
+            all_factors.extend(relation.factors.iter());
+            large_primes.extend([relation.large.0, relation.large.1]);
+        }
+        all_factors.extend(large_primes.into_iter().filter(|p| *p != 1).map(|p| (p, 1)));
+        let mut all_factors = merge_tuples(&all_factors);
+        all_factors.sort();
+        Some((product, all_factors))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_large_primes() {
+        let (largest, bound) = (100, 10000);
+        assert_eq!(large_primes(&Integer::from(1), largest, bound, 0), Some((1, 1)));
+        assert_eq!(large_primes(&Integer::from(101), largest, bound, 0), None);
+        assert_eq!(large_primes(&Integer::from(101), largest, bound, 1), Some((101, 1)));
+        assert_eq!(large_primes(&Integer::from(9973), largest, bound, 2), Some((9973, 1)));
+        assert_eq!(large_primes(&Integer::from(10007), largest, bound, 2), None);
+        assert_eq!(large_primes(&Integer::from(9973*101), largest, bound, 1), None);
+        assert_eq!(large_primes(&Integer::from(9973*101), largest, bound, 2), Some((101, 9973)));
+        assert_eq!(large_primes(&Integer::from(103*103), largest, bound, 2), Some((103, 103)));
+        assert_eq!(large_primes(&Integer::from(10007*101), largest, bound, 2), None);
+        assert_eq!(large_primes(&Integer::from(101*103*107), largest, 1 << 20, 2), None);
+        assert_eq!(large_prime_bound(1 << 30), 1 << 30);
+        // Too large for squfof, which is never reached with the largest bound.
+        assert_eq!(large_primes(&Integer::from((1u64 << 62) + 1), 1 << 20, large_prime_bound(1 << 30), 2), None);
+    }
+
+
+    #[test]
+    fn test_partial_relations() {
+        let n = Integer::from(1000003*1000033u64);
+        let mut partials = PartialRelations::new();
+        assert!(partials.is_empty());
+
+        // Two relations with the same large prime.
+        assert_eq!(partials.add(&n, Integer::from(5), vec![(2, 1)], (101, 1)), None);
+        assert_eq!(partials.add(&n, Integer::from(5), vec![(2, 1)], (101, 1)), None);
+        assert_eq!(partials.add(&n, Integer::from(7), vec![(2, 1), (3, 1)], (101, 1)), Some((Integer::from(35), vec![(2, 2), (3, 1), (101, 2)])));
+
+        // A cycle 103 - 107 - 109 - 103 of double large prime relations.
+        assert_eq!(partials.add(&n, Integer::from(11), vec![(3, 1)], (103, 107)), None);
+        assert_eq!(partials.add(&n, Integer::from(13), vec![(5, 1)], (107, 109)), None);
+        assert_eq!(partials.add(&n, Integer::from(17), vec![(3, 1), (5, 1)], (103, 109)), Some((Integer::from(11*13*17), vec![(3, 2), (5, 2), (103, 2), (107, 2), (109, 2)])));
+
+        // Joining the tree of 101 to that of 103, and closing a cycle through 1 on both.
+        assert_eq!(partials.add(&n, Integer::from(19), vec![], (101, 103)), None);
+        assert_eq!(partials.add(&n, Integer::from(23), vec![(7, 1)], (107, 1)), Some((Integer::from(5*19*11*23), vec![(2, 1), (3, 1), (7, 1), (101, 2), (103, 2), (107, 2)])));
+
+        // A cofactor which is a square.
+        assert_eq!(partials.add(&n, Integer::from(29), vec![(2, 1)], (113, 113)), Some((Integer::from(29), vec![(2, 1), (113, 2)])));
+        assert_eq!(partials.len(), 4);
+    }
+}
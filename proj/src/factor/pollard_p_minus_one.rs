@@ -0,0 +1,138 @@
+use rug::{Complete, Integer};
+use crate::integers::integer_computations::pow_rug;
+use crate::integers::sieve::PrimeTable;
+
+
+// The gcd is only taken every GCD_INTERVAL primes. If it is n, the last interval is redone one
+// prime at a time, so the factors found in it can still be separated.
+pub(crate) const GCD_INTERVAL: usize = 100;
+
+
+// What a method finding p | n with a smooth group order p - 1, p + 1, ... gives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmoothnessResult {
+    Factor(Integer),
+    // Every prime factor of n was found with the same prime power, so the gcd is n itself.
+    AllFactorsAtOnce,
+    // No group order was smooth enough, B1 and B2 must be increased to go on.
+    BoundsExhausted,
+}
+
+
+// A nontrivial gcd(x, n), or the reason there is none.
+pub(crate) fn check_gcd(x: &Integer, n: &Integer) -> Option<SmoothnessResult> {
+    let g = x.gcd_ref(n).complete();
+    if g == *n {
+        Some(SmoothnessResult::AllFactorsAtOnce)
+    } else if g != 1 {
+        Some(SmoothnessResult::Factor(g))
+    } else {
+        None
+    }
+}
+
+
+// The largest power q^e <= bound.
+pub(crate) fn largest_power_below(q: u64, bound: u64) -> Integer {
+    let mut power = q;
+    while power <= bound/q {
+        power *= q;
+    }
+    Integer::from(power)
+}
+
+
+// Pollard's p - 1 with base a. For a prime p | n with p - 1 dividing E = prod_{q <= B1} q^e, where
+// q^e is the largest power of q below B1, we have a^E = 1 (mod p) by Fermat's little theorem, and
+// p | gcd(a^E - 1, n). Stage 2 allows p - 1 to have one more prime factor q in (B1, B2], found by
+// walking b = a^(Eq) along the primes, multiplying by the precomputed a^(E(q' - q)) for the gaps.
+pub fn pollard_p_minus_one(n: &Integer, a: &Integer, b1: u64, b2: u64) -> SmoothnessResult {
+    if n.is_even() {
+        return SmoothnessResult::Factor(Integer::from(2));
+    }
+    let primes = PrimeTable::up_to(b1.max(b2));
+    let (stage_one, stage_two) = primes.as_slice().split_at(primes.as_slice().partition_point(|q| *q <= b1));
+
+    let mut x = a.clone().modulo(n);
+    for chunk in stage_one.chunks(GCD_INTERVAL) {
+        let start = x.clone();
+        for q in chunk {
+            x = pow_rug(&x, &largest_power_below(*q, b1), n);
+        }
+        match check_gcd(&(&x - 1u32).complete(), n) {
+            Some(SmoothnessResult::AllFactorsAtOnce) => {
+                x = start;
+                for q in chunk {
+                    x = pow_rug(&x, &largest_power_below(*q, b1), n);
+                    if let Some(result) = check_gcd(&(&x - 1u32).complete(), n) {
+                        return result;
+                    }
+                }
+            },
+            Some(result) => return result,
+            None => (),
+        }
+    }
+
+    let first = match stage_two.first() {
+        Some(q) => *q,
+        None => return SmoothnessResult::BoundsExhausted,
+    };
+    // gaps[d/2] = x^d for the even gaps d between consecutive primes.
+    let largest_gap = stage_two.windows(2).map(|w| w[1] - w[0]).max().unwrap_or(0);
+    let x_squared = x.square_ref().complete().modulo(n);
+    let mut gaps = vec![Integer::ONE.clone()];
+    for _ in 0..largest_gap/2 {
+        let next = (gaps.last().expect("Starts with x^0")*&x_squared).complete().modulo(n);
+        gaps.push(next);
+    }
+
+    let mut b = pow_rug(&x, &Integer::from(first), n);
+    let mut product = (&b - 1u32).complete().modulo(n);
+    for (i, pair) in stage_two.windows(2).enumerate() {
+        b = (b*&gaps[((pair[1] - pair[0])/2) as usize]).modulo(n);
+        product = (product*(&b - 1u32).complete()).modulo(n);
+        if i % GCD_INTERVAL == 0 || i + 2 == stage_two.len() {
+            if let Some(result) = check_gcd(&product, n) {
+                return result;
+            }
+        }
+    }
+
+    check_gcd(&product, n).unwrap_or(SmoothnessResult::BoundsExhausted)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::factor::test_helpers::{assert_factor, find_prime_with_smooth_neighbour};
+    use crate::integers::prime::find_strong_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_pollard_p_minus_one() {
+        let two = Integer::from(2);
+        let loops = 5;
+        for _ in 0..loops {
+            let p = find_prime_with_smooth_neighbour(80, 1000, 1, -1);
+            let q = find_strong_prime_with_bit_length(128, None, 30, 1000);
+            let n = (&p*&q).complete();
+            assert_factor(pollard_p_minus_one(&n, &two, 1000, 1000), &p);
+
+            // 50021 is prime, so p - 1 is only found in stage 2.
+            let p = find_prime_with_smooth_neighbour(80, 1000, 50021, -1);
+            let n = (&p*&q).complete();
+            assert_eq!(pollard_p_minus_one(&n, &two, 1000, 1000), SmoothnessResult::BoundsExhausted);
+            assert_factor(pollard_p_minus_one(&n, &two, 1000, 100000), &p);
+        }
+
+        // Both factors are found with the last prime power, and the backtracking separates them.
+        let p = find_prime_with_smooth_neighbour(40, 100, 1, -1);
+        let r = find_prime_with_smooth_neighbour(40, 100, 1, -1);
+        let result = pollard_p_minus_one(&(&p*&r).complete(), &two, 100, 100);
+        assert!(result == SmoothnessResult::Factor(p.clone()) || result == SmoothnessResult::Factor(r.clone()) || result == SmoothnessResult::AllFactorsAtOnce);
+
+        assert_eq!(pollard_p_minus_one(&Integer::from(1000), &two, 10, 10), SmoothnessResult::Factor(two));
+    }
+}
@@ -0,0 +1,154 @@
+use rug::{rand::RandState, Assign, Complete, Integer};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+// The differences |x - y| are multiplied together modulo n and only every BATCH_SIZE steps is a
+// gcd taken, which costs about as much as the batch itself.
+const BATCH_SIZE: usize = 128;
+
+
+fn step(x: &mut Integer, c: &Integer, n: &Integer) {
+    x.square_mut();
+    *x += c;
+    *x %= n;
+}
+
+
+// Pollard's rho with Brent's cycle detection for the sequence x_(i+1) = x_i^2 + c (mod n), which
+// is eventually periodic modulo every prime p | n, with period about sqrt(p). The tortoise x is
+// moved to the hare y at each power of two r, and the hare walks r steps, so a collision modulo p
+// is found after O(sqrt(p)) steps. Returns None if n itself is found, which happens when the
+// cycles modulo all factors close together, or after max_iterations steps.
+pub fn pollard_rho_brent(n: &Integer, x0: &Integer, c: &Integer, max_iterations: usize) -> Option<Integer> {
+    pollard_rho_brent_counting_iterations(n, x0, c, max_iterations).0
+}
+
+
+// Also returns the number of steps of the sequence taken.
+fn pollard_rho_brent_counting_iterations(n: &Integer, x0: &Integer, c: &Integer, max_iterations: usize) -> (Option<Integer>, usize) {
+    let mut y = x0.clone();
+    let mut x = y.clone();
+    let mut ys = y.clone();
+    let mut q = Integer::ONE.clone();
+    let mut difference = Integer::new();
+    let mut g = Integer::ONE.clone();
+    let mut r = 1;
+    let mut iterations = 0;
+
+    while g == 1 {
+        x = y.clone();
+        for _ in 0..r {
+            step(&mut y, c, n);
+        }
+        let mut k = 0;
+        while k < r && g == 1 {
+            ys = y.clone();
+            for _ in 0..BATCH_SIZE.min(r - k) {
+                step(&mut y, c, n);
+                // The sign of the product does not matter for the gcd.
+                difference.assign(&x - &y);
+                q *= &difference;
+                q %= n;
+            }
+            g = q.gcd_ref(n).complete();
+            k += BATCH_SIZE;
+        }
+        iterations += 2*r;
+        if iterations > max_iterations && g == 1 {
+            return (None, iterations);
+        }
+        r *= 2;
+    }
+
+    // The batch ending in a factor may also contain n, in which case it is walked one step at a
+    // time from its start.
+    if g == *n {
+        loop {
+            step(&mut ys, c, n);
+            g = (&x - &ys).complete().abs().gcd(n);
+            if g != 1 {
+                break;
+            }
+        }
+    }
+
+    if g == *n {
+        (None, iterations)
+    } else {
+        (Some(g), iterations)
+    }
+}
+
+
+// A factor 1 < d < n of the composite n, trying random starting points and constants until one
+// works. Gives up after max_iterations steps in total.
+pub fn find_factor_by_pollard_rho(n: &Integer, max_iterations: usize) -> Option<Integer> {
+    if *n < 4 {
+        return None;
+    }
+    if n.is_even() {
+        return Some(Integer::from(2));
+    }
+
+    let mut rng = RandState::new();
+    rng.seed(&Integer::from(SystemTime::now().duration_since(UNIX_EPOCH).expect("We are not time travelling").as_nanos()));
+    let n_minus_three = (n - 3u32).complete();
+
+    let mut iterations = 0;
+    while iterations < max_iterations {
+        let x0 = n.random_below_ref(&mut rng).complete();
+        // c = 0 and c = -2 give sequences which are easily predicted and often fail.
+        let c = n_minus_three.random_below_ref(&mut rng).complete() + 1u32;
+        let (factor, used) = pollard_rho_brent_counting_iterations(n, &x0, &c, max_iterations - iterations);
+        if factor.is_some() {
+            return factor;
+        }
+        iterations += used;
+    }
+
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::factor::factorization::factorize;
+    use crate::integers::prime::find_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_pollard_rho_brent() {
+        // 8051 = 83*97 is the example of Pollard's paper.
+        let n = Integer::from(8051);
+        let factor = pollard_rho_brent(&n, &Integer::from(2), &Integer::from(1), 1000).unwrap();
+        assert!(factor == 83 || factor == 97, "Found {} as a factor of 8051", factor);
+
+        for n in [4u64, 9, 15, 25, 91, 1001, 10403, 999999999989*3] {
+            let n = Integer::from(n);
+            let factor = find_factor_by_pollard_rho(&n, 1 << 20).unwrap();
+            assert!(factor != 1 && factor != n && n.is_divisible(&factor), "Found {} as a factor of {}", factor, n);
+        }
+
+        assert_eq!(find_factor_by_pollard_rho(&Integer::from(1000003), 1000), None);
+    }
+
+
+    #[test]
+    fn test_pollard_rho_with_medium_factors() {
+        let loops = 5;
+        for _ in 0..loops {
+            let p = find_prime_with_bit_length(32, 30);
+            let q = find_prime_with_bit_length(100, 30);
+            let n = (&p*&q).complete();
+            let factor = find_factor_by_pollard_rho(&n, 1 << 22).unwrap();
+            assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, n);
+
+            let r = find_prime_with_bit_length(28, 30);
+            let m = (&n*&r).complete();
+            let mut expected = vec![(p.clone(), 1), (q.clone(), 1), (r.clone(), 1)];
+            expected.sort();
+            assert_eq!(factorize(&m), expected);
+        }
+    }
+}
@@ -0,0 +1,548 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use rand::{thread_rng, Rng};
+use rayon::prelude::*;
+use rug::{Complete, Integer};
+use crate::factor::checkpoint::{Checkpoint, Method};
+use crate::factor::factor_base::{automatic_size, FactorBase, FactorBasePrime, EXTRA_RELATIONS};
+use crate::factor::large_prime::{large_prime_bound, large_primes, LARGE_PRIMES};
+use crate::factor::random_squares::{factor_from_square, find_all_squares_by_relations, merge_tuples, MINUS_ONE};
+use crate::factor::relation_store::RelationStore;
+use crate::integers::integer_computations::{mul_mod_u64, pow_mod_u64};
+use crate::integers::prime::baillie_psw_is_prime;
+use crate::integers::sieve::PrimeTable;
+
+
+// Odd squarefree multipliers k tried by Knuth-Schroeppel.
+pub(crate) const MULTIPLIERS: [u64; 31] = [
+    1, 3, 5, 7, 11, 13, 15, 17, 19, 21, 23, 29, 31, 33, 35, 37, 39, 41, 43, 47, 51, 53, 55, 57, 59,
+    61, 65, 67, 69, 71, 73,
+];
+
+// (digits of n, M) where the sieve interval is [-M, M), loosely after the parameters in Contini's
+// thesis. The size of the factor base is chosen by FactorBase itself.
+const SIQS_PARAMETERS: [(usize, usize); 13] = [
+    (20, 8192),
+    (25, 16384),
+    (30, 16384),
+    (35, 32768),
+    (40, 32768),
+    (45, 32768),
+    (50, 65536),
+    (55, 65536),
+    (60, 65536),
+    (65, 98304),
+    (70, 98304),
+    (75, 131072),
+    (80, 131072),
+];
+
+// Primes below this are not sieved, as they hit most positions while adding little to the sum.
+// What they would have added is left to the slack that CLOSENESS gives the threshold.
+const SMALL_PRIME_BOUND: u64 = 30;
+
+// A position is trial divided if its sum of logarithms is within this many logarithms of the
+// largest prime in the factor base from log |Q(x)|.
+const CLOSENESS: f64 = 1.8;
+
+// How much further below the threshold a position may be for each large prime allowed.
+const LARGE_PRIME_CLOSENESS: f64 = 0.25;
+
+// The number of tries for a new value of a before choose_a gives up, and how many times the
+// sieve interval may be doubled once the values of a run out.
+const A_ATTEMPTS: usize = 1000;
+const MAX_INTERVAL_DOUBLINGS: usize = 4;
+
+
+// The k maximising the expected contribution of small primes to log |t^2 - kn| minus the loss of
+// log sqrt(k) in size. An odd prime p with (kn/p) = 1 divides a random t^2 - kn twice with
+// probability 2/(p - 1) in all, one dividing k once with probability 1/p, and 2 contributes
+// more the better kn is modulo 8.
+pub fn knuth_schroeppel(n: &Integer) -> u64 {
+    let primes = PrimeTable::first(300);
+    let mut best = (f64::MIN, 1);
+    for k in MULTIPLIERS {
+        let kn = (n*k).complete();
+        let mut score = -0.5*(k as f64).ln() + match kn.mod_u(8) {
+            1 => 2.0,
+            5 => 1.0,
+            _ => 0.5,
+        }*2f64.ln();
+        for p in primes.iter().skip(1) {
+            let log_p = (p as f64).ln();
+            if k % p == 0 {
+                score += log_p/p as f64;
+            } else if kn.mod_u(p as u32) != 0 && pow_mod_u64(kn.mod_u(p as u32) as u64, (p - 1)/2, p) == 1 {
+                score += 2.0*log_p/(p - 1) as f64;
+            }
+        }
+        if score > best.0 {
+            best = (score, k);
+        }
+    }
+    best.1
+}
+
+
+// A polynomial Q(x) = ((ax + b)^2 - kn)/a = ax^2 + 2bx + c, where a is a product of s primes of
+// the factor base and b^2 = kn (mod a). Then (ax + b)^2 = aQ(x) (mod n), and a is chosen close to
+// sqrt(2kn)/M so that |Q(x)| <= M sqrt(kn/2) on [-M, M).
+struct Polynomial {
+    a: Integer,
+    b: Integer,
+    c: Integer,
+    // The indices of the factors of a in the factor base.
+    factors: Vec<usize>,
+}
+
+
+// Chooses s primes of about the same size q with q^s close to the target, the last one to bring
+// a as close as possible, and never the same set twice. Taking q large keeps down the number of
+// primes which are not sieved with, as they divide a. Primes dividing k are left out, as their
+// root 0 would give every b twice. Returns None if no new set is found in A_ATTEMPTS tries.
+fn choose_a(target: &Integer, base: &FactorBase, used: &Mutex<HashSet<Vec<usize>>>) -> Option<Vec<usize>> {
+    let mut rng = thread_rng();
+    let k = base.multiplier();
+    let base = base.as_slice();
+    let allowed = |i: &usize| !k.is_multiple_of(base[*i].p);
+    let first = base.iter().position(|q| q.p > SMALL_PRIME_BOUND).unwrap_or(0);
+    let bits = target.significant_bits() as f64;
+    let s = (bits/(base[3*base.len()/4].p as f64).log2()).ceil().max(1.0) as usize;
+    let ideal = 2f64.powf(bits/s as f64);
+    let center = base.iter().position(|q| q.p as f64 >= ideal).unwrap_or(base.len() - 1);
+    let width = (base.len()/10).max(s + 10);
+    let (smallest, largest) = (center.saturating_sub(width).max(first), (center + width).min(base.len()));
+    if (smallest..largest).filter(allowed).count() < s {
+        return None;
+    }
+
+    for attempts in 1..=A_ATTEMPTS {
+        let mut factors: Vec<usize> = Vec::with_capacity(s);
+        let mut product = Integer::ONE.clone();
+        while factors.len() + 1 < s {
+            let i = rng.gen_range(smallest..largest);
+            if allowed(&i) && !factors.contains(&i) {
+                factors.push(i);
+                product *= base[i].p;
+            }
+        }
+        let remaining = (target/&product).complete().to_f64();
+        let last = (first..base.len())
+            .filter(|i| allowed(i) && !factors.contains(i))
+            .min_by(|i, j| (base[*i].p as f64 - remaining).abs().total_cmp(&(base[*j].p as f64 - remaining).abs()))
+            .expect("There are more than s candidates");
+        // If the closest choice keeps repeating, the last factor is taken at random.
+        factors.push(if attempts < 100 { last } else { rng.gen_range(smallest..largest) });
+        factors.sort();
+        factors.dedup();
+        if factors.len() == s && factors.iter().all(allowed) && used.lock().expect("No thread panics holding the used values of a").insert(factors.clone()) {
+            return Some(factors);
+        }
+    }
+    None
+}
+
+
+// The sieve of one value of a. With B_l = (a/q_l)(t_l (a/q_l)^-1 mod q_l) for the roots t_l of kn
+// modulo q_l, all 2^(s-1) values b = B_1 +- B_2 +- ... +- B_s have b^2 = kn (mod a). They are run
+// through in Gray code order, so that going to the next b only adds 2B_v, and every root of Q
+// modulo p moves by 2B_v a^-1.
+struct SelfInitialisingSieve<'a> {
+    n: &'a Integer,
+    kn: Integer,
+    base: &'a [FactorBasePrime],
+    m: usize,
+    polynomial: Polynomial,
+    b_terms: Vec<Integer>,
+    // Which primes to sieve with, excluding the small ones, the factors of a and those of k.
+    sieved: Vec<bool>,
+    roots: Vec<(u64, u64)>,
+    // root_steps[l][i] = 2 B_l a^-1 modulo p_i
+    root_steps: Vec<Vec<u64>>,
+    index: usize,
+}
+
+
+impl<'a> SelfInitialisingSieve<'a> {
+    fn new(n: &'a Integer, k: u64, base: &'a [FactorBasePrime], m: usize, factors: Vec<usize>) -> SelfInitialisingSieve<'a> {
+        let kn = (n*k).complete();
+        let a: Integer = factors.iter().map(|i| Integer::from(base[*i].p)).product();
+
+        let mut b_terms = Vec::with_capacity(factors.len());
+        for i in factors.iter() {
+            let q = base[*i].p;
+            let a_over_q = (&a/q).complete();
+            let inverse = pow_mod_u64(a_over_q.mod_u(q as u32) as u64, q - 2, q);
+            let mut gamma = mul_mod_u64(base[*i].root, inverse, q);
+            if gamma > q/2 {
+                gamma = q - gamma;
+            }
+            b_terms.push(a_over_q*gamma);
+        }
+        let b: Integer = b_terms.iter().sum();
+
+        let sieved: Vec<bool> = (0..base.len())
+            .map(|i| base[i].p > SMALL_PRIME_BOUND && !k.is_multiple_of(base[i].p) && !factors.contains(&i))
+            .collect();
+        let mut roots = vec![(0, 0); base.len()];
+        let mut root_steps = vec![vec![0; base.len()]; factors.len()];
+        for (i, prime) in base.iter().enumerate() {
+            if !sieved[i] {
+                continue;
+            }
+            let p = prime.p;
+            let a_inverse = pow_mod_u64(a.mod_u(p as u32) as u64, p - 2, p);
+            for (l, term) in b_terms.iter().enumerate() {
+                root_steps[l][i] = mul_mod_u64(2*term.mod_u(p as u32) as u64, a_inverse, p);
+            }
+            let b_mod_p = b.mod_u(p as u32) as u64;
+            let shift = m as u64 % p;
+            let root1 = mul_mod_u64(a_inverse, (prime.root + p - b_mod_p) % p, p);
+            let root2 = mul_mod_u64(a_inverse, (2*p - prime.root - b_mod_p) % p, p);
+            roots[i] = ((root1 + shift) % p, (root2 + shift) % p);
+        }
+
+        let c = (b.square_ref().complete() - &kn)/&a;
+        SelfInitialisingSieve {
+            n,
+            kn,
+            base,
+            m,
+            polynomial: Polynomial { a, b, c, factors },
+            b_terms,
+            sieved,
+            roots,
+            root_steps,
+            index: 0,
+        }
+    }
+
+
+    // Goes to the next b, or returns false when all 2^(s-1) have been used.
+    fn next_polynomial(&mut self) -> bool {
+        self.index += 1;
+        if self.index >= 1 << (self.b_terms.len() - 1) {
+            return false;
+        }
+        // b_(i+1) = b_i + 2 (-1)^ceil(i/2^v) B_v for 2^v || 2i.
+        let v = self.index.trailing_zeros() as usize;
+        let negative = self.index.div_ceil(1 << (v + 1)) % 2 == 1;
+        let term = (&self.b_terms[v]*2u32).complete();
+        if negative {
+            self.polynomial.b -= term;
+        } else {
+            self.polynomial.b += term;
+        }
+        for (i, prime) in self.base.iter().enumerate() {
+            if !self.sieved[i] {
+                continue;
+            }
+            let (p, step) = (prime.p, self.root_steps[v][i]);
+            let (root1, root2) = self.roots[i];
+            let step = if negative { step } else { p - step };
+            self.roots[i] = ((root1 + step) % p, (root2 + step) % p);
+        }
+        let polynomial = &mut self.polynomial;
+        polynomial.c = (polynomial.b.square_ref().complete() - &self.kn)/&polynomial.a;
+        true
+    }
+
+
+    // Sums log p over the roots of Q modulo p in [-M, M) and trial divides the positions
+    // reaching the threshold, giving (ax + b mod n, factors of aQ(x)). Those left with up to
+    // `max_large_primes` large primes are added to the store as partial relations.
+    fn sieve(&self, threshold: u8, max_large_primes: usize, store: &RelationStore) {
+        let largest = self.base.last().expect("The factor base is not empty").p;
+        let bound = large_prime_bound(largest);
+        let mut sums = vec![0u8; 2*self.m];
+        for (i, prime) in self.base.iter().enumerate() {
+            if !self.sieved[i] {
+                continue;
+            }
+            let (p, log) = (prime.p as usize, prime.log);
+            for root in [self.roots[i].0, self.roots[i].1] {
+                let mut j = root as usize;
+                while j < sums.len() {
+                    sums[j] = sums[j].wrapping_add(log);
+                    j += p;
+                }
+            }
+        }
+
+        for (j, sum) in sums.iter().enumerate() {
+            if *sum < threshold {
+                continue;
+            }
+            let x = j as i64 - self.m as i64;
+            let polynomial = &self.polynomial;
+            let mut q = ((&polynomial.a*x).complete() + 2u32*&polynomial.b)*x + &polynomial.c;
+            if q.is_zero() {
+                continue;
+            }
+
+            let mut factors: Vec<(u64, u64)> = polynomial.factors.iter().map(|i| (self.base[*i].p, 1)).collect();
+            if q < 0 {
+                factors.push((MINUS_ONE, 1));
+                q = -q;
+            }
+            for (i, prime) in self.base.iter().enumerate() {
+                let p = prime.p;
+                if self.sieved[i] && (j as u64 % p != self.roots[i].0 && j as u64 % p != self.roots[i].1) {
+                    continue;
+                }
+                let mut exponent = 0;
+                while q.is_divisible_u(p as u32) {
+                    q /= p as u32;
+                    exponent += 1;
+                }
+                if exponent > 0 {
+                    factors.push((p, exponent));
+                }
+            }
+
+            // The same value of t^2 - kn is found from every a dividing it, with either sign of t.
+            let t = ((&polynomial.a*x).complete() + &polynomial.b).abs().modulo(self.n);
+            if let Some(large) = large_primes(&q, largest, bound, max_large_primes) {
+                store.add(t, merge_tuples(&factors), large);
+            }
+        }
+    }
+}
+
+
+// M for n from SIQS_PARAMETERS, taking the first row for at least as many digits.
+fn sieve_half_width(n: &Integer) -> usize {
+    let digits = n.to_string().len();
+    SIQS_PARAMETERS.iter().find(|row| row.0 >= digits).unwrap_or(&SIQS_PARAMETERS[SIQS_PARAMETERS.len() - 1]).1
+}
+
+
+// Relations t^2 = prod p^e (mod n) from the self-initialising quadratic sieve with multiplier k,
+// until there are `count` of them.
+pub fn find_relations_by_quadratic_sieve(n: &Integer, k: u64, count: usize) -> Result<HashMap<Integer, Vec<(u64, u64)>>, Integer> {
+    find_relations_by_quadratic_sieve_with_large_primes(n, k, count, LARGE_PRIMES)
+}
+
+
+// As find_relations_by_quadratic_sieve, with partial relations of up to `max_large_primes` large
+// primes combined into full ones. The threshold is lowered by LARGE_PRIME_CLOSENESS logarithms of
+// the largest prime for each large prime, so that they reach trial division.
+pub fn find_relations_by_quadratic_sieve_with_large_primes(n: &Integer, k: u64, count: usize, max_large_primes: usize) -> Result<HashMap<Integer, Vec<(u64, u64)>>, Integer> {
+    let base = FactorBase::with_automatic_size(n, k)?;
+    let store = RelationStore::new(n, count);
+    find_relations_by_quadratic_sieve_into_store(n, &base, &store, max_large_primes);
+    Ok(store.into_relations())
+}
+
+
+// Every worker of the rayon pool sieves with its own values of a until the store has its target
+// of relations, which another thread can follow through the store meanwhile. For small n the
+// store may end up short of its target, if every a has been used with the longest interval.
+pub fn find_relations_by_quadratic_sieve_into_store(n: &Integer, base: &FactorBase, store: &RelationStore, max_large_primes: usize) {
+    let k = base.multiplier();
+    let kn = (n*k).complete();
+    let largest_prime = base.largest().expect("The factor base is not empty") as f64;
+    let closeness = CLOSENESS + LARGE_PRIME_CLOSENESS*max_large_primes as f64;
+
+    let used: Mutex<HashSet<Vec<usize>>> = Mutex::new(HashSet::new());
+    let doublings = AtomicUsize::new(0);
+    (0..u64::MAX).into_par_iter()
+        .find_any(|_| {
+            let (m, factors) = loop {
+                let current = doublings.load(Ordering::Relaxed);
+                let m = sieve_half_width(n) << current;
+                let target = ((&kn*2u32).complete().sqrt()/m as u64).max(Integer::from(2));
+                if let Some(factors) = choose_a(&target, base, &used) {
+                    break (m, factors);
+                }
+                // Small n have few values of a, and once they are used up every a is sieved again
+                // over an interval twice as long.
+                if current == MAX_INTERVAL_DOUBLINGS {
+                    return true;
+                }
+                if doublings.compare_exchange(current, current + 1, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+                    used.lock().expect("No thread panics holding the used values of a").clear();
+                }
+            };
+            let log_q = (kn.to_f64()/2.0).log2()/2.0 + (m as f64).log2();
+            let threshold = (log_q - closeness*largest_prime.log2()).max(1.0) as u8;
+            let mut sieve = SelfInitialisingSieve::new(n, k, base.as_slice(), m, factors);
+            loop {
+                sieve.sieve(threshold, max_large_primes, store);
+                if store.is_done() || !sieve.next_polynomial() {
+                    break;
+                }
+            }
+            store.is_done()
+        });
+}
+
+
+// A proper factor of the odd composite n, which should not be a perfect power, from relations
+// (ax + b)^2 = aQ(x) (mod n) found by the quadratic sieve. They are put through the same linear
+// algebra as the relations of random squares. Returns None if n is prime, if too few relations
+// are found or every dependency gives a trivial factor.
+pub fn find_factor_by_quadratic_sieve(n: &Integer) -> Option<Integer> {
+    if *n < 4 || baillie_psw_is_prime(n) {
+        return None;
+    }
+    if n.is_even() {
+        return Some(Integer::from(2));
+    }
+    let k = knuth_schroeppel(n);
+    let relations = match find_relations_by_quadratic_sieve(n, k, automatic_size(n) + EXTRA_RELATIONS) {
+        Ok(relations) => relations,
+        Err(factor) => return Some(factor),
+    };
+
+    find_all_squares_by_relations(&relations)
+        .iter()
+        .map(|square| factor_from_square(n, &relations, square))
+        .find(|factor| *factor != 1 && factor != n)
+}
+
+
+// As find_factor_by_quadratic_sieve, saving the job to `path` every `interval` while the relations
+// are collected, once they are all found, and with the factor at the end.
+pub fn find_factor_by_quadratic_sieve_with_checkpoint(n: &Integer, path: &Path, interval: Duration) -> Option<Integer> {
+    if *n < 4 || baillie_psw_is_prime(n) {
+        return None;
+    }
+    if n.is_even() {
+        return Some(Integer::from(2));
+    }
+    let method = Method::QuadraticSieve { multiplier: knuth_schroeppel(n), base_size: automatic_size(n) };
+    let job = Checkpoint::new(n, method, LARGE_PRIMES, automatic_size(n) + EXTRA_RELATIONS);
+    job.write_or_report(path);
+    run_quadratic_sieve_job(job, path, interval)
+}
+
+
+// Goes on with the job saved at `path` by find_factor_by_quadratic_sieve_with_checkpoint, from the
+// relations it had, or gives the factor if it was already found.
+pub fn resume_quadratic_sieve(path: &Path, interval: Duration) -> Result<Option<Integer>, String> {
+    let job = Checkpoint::read(path)?;
+    if !matches!(job.method, Method::QuadraticSieve { .. }) {
+        return Err(format!("{} is not a job of the quadratic sieve", path.display()));
+    }
+    Ok(run_quadratic_sieve_job(job, path, interval))
+}
+
+
+fn run_quadratic_sieve_job(job: Checkpoint, path: &Path, interval: Duration) -> Option<Integer> {
+    let Method::QuadraticSieve { multiplier, base_size } = job.method else {
+        panic!("The job is one of the quadratic sieve");
+    };
+    let Some(n) = job.remaining.last().cloned() else {
+        return job.factors.first().cloned();
+    };
+    let base = match FactorBase::new(&n, multiplier, base_size) {
+        Ok(base) => base,
+        Err(factor) => return Some(factor),
+    };
+    let store = RelationStore::from_checkpoint(&job, path, Some(interval));
+    find_relations_by_quadratic_sieve_into_store(&n, &base, &store, job.max_large_primes);
+    let mut job = store.to_checkpoint(&job);
+    job.write_or_report(path);
+
+    let relations = store.into_relations();
+    let factor = find_all_squares_by_relations(&relations)
+        .iter()
+        .map(|square| factor_from_square(&n, &relations, square))
+        .find(|factor| *factor != 1 && *factor != n)?;
+    job.factors = vec![factor.clone(), (&n/&factor).complete()];
+    job.remaining.clear();
+    job.relations.clear();
+    job.partials.clear();
+    job.write_or_report(path);
+    Some(factor)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::factor::factorization::factorize;
+    use crate::factor::test_helpers::check_relation;
+    use crate::integers::prime::find_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_relations_by_quadratic_sieve() {
+        let p = find_prime_with_bit_length(50, 30);
+        let q = find_prime_with_bit_length(50, 30);
+        let n = (&p*&q).complete();
+        let k = knuth_schroeppel(&n);
+        assert!(MULTIPLIERS.contains(&k));
+
+        for max_large_primes in [0, 1, 2] {
+            let relations = find_relations_by_quadratic_sieve_with_large_primes(&n, k, 100, max_large_primes).unwrap();
+            assert!(relations.len() >= 100);
+            for (t, factors) in relations.iter() {
+                check_relation(&n, t, factors);
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_find_factor_by_quadratic_sieve() {
+        let loops = 3;
+        for bits in [40, 50, 60] {
+            for _ in 0..loops {
+                let p = find_prime_with_bit_length(bits, 30);
+                let q = find_prime_with_bit_length(bits + 5, 30);
+                let n = (&p*&q).complete();
+                let factor = find_factor_by_quadratic_sieve(&n).unwrap();
+                assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, n);
+            }
+        }
+        assert_eq!(find_factor_by_quadratic_sieve(&find_prime_with_bit_length(100, 30)), None);
+
+        // Small n with so few values of a that they run out.
+        for (p, q) in [(712447u64, 773447u64), (931639, 1328981)] {
+            let n = Integer::from(p*q);
+            let factor = find_factor_by_quadratic_sieve(&n).unwrap();
+            assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, n);
+        }
+
+        // Rho and ECM are not meant to find a factor of 80 bits.
+        let p = find_prime_with_bit_length(80, 30);
+        let q = find_prime_with_bit_length(80, 30);
+        let r = find_prime_with_bit_length(20, 30);
+        let mut expected = vec![(p.clone(), 1), (q.clone(), 1), (r.clone(), 2)];
+        expected.sort();
+        let n = (&p*&q).complete()*&r*&r;
+        assert_eq!(factorize(&n), expected);
+    }
+
+
+    #[test]
+    fn test_quadratic_sieve_with_checkpoint() {
+        let p = find_prime_with_bit_length(60, 30);
+        let q = find_prime_with_bit_length(65, 30);
+        let n = (&p*&q).complete();
+        let path = std::env::temp_dir().join(format!("quadratic-sieve-test-{}", std::process::id()));
+        let interval = Duration::from_millis(10);
+
+        // A job killed with half of the relations.
+        let k = knuth_schroeppel(&n);
+        let method = Method::QuadraticSieve { multiplier: k, base_size: automatic_size(&n) };
+        let mut job = Checkpoint::new(&n, method, LARGE_PRIMES, automatic_size(&n) + EXTRA_RELATIONS);
+        job.relations = find_relations_by_quadratic_sieve(&n, k, job.target/2).unwrap().into_iter().collect();
+        job.write(&path).unwrap();
+        let factor = resume_quadratic_sieve(&path, interval).unwrap().unwrap();
+        assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, n);
+        let job = Checkpoint::read(&path).unwrap();
+        assert!(job.remaining.is_empty() && job.relations.is_empty());
+        assert_eq!(job.factors, vec![factor.clone(), (&n/&factor).complete()]);
+        assert_eq!(resume_quadratic_sieve(&path, interval), Ok(Some(factor)));
+
+        let factor = find_factor_by_quadratic_sieve_with_checkpoint(&n, &path, interval).unwrap();
+        assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, n);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
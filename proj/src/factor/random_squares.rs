@@ -1,41 +1,55 @@
-use gauss_jordan_elimination::gauss_elimination_generic;
 use itertools::Itertools;
-use rug::{integer::IsPrime, rand::RandState, Complete, Integer};
-use std::{collections::HashMap, fs, hash::Hash, io::{BufRead, BufReader}, ops::AddAssign, time::{SystemTime, UNIX_EPOCH}};
-use crate::algebraic_structure::z2::Z2;
-use num::traits::{Zero, One};
+use rayon::prelude::*;
+use rug::{Complete, Integer};
+use std::{collections::HashMap, hash::Hash, ops::AddAssign, path::Path, time::Duration};
+use crate::factor::checkpoint::{Checkpoint, Method};
+use crate::factor::factorization::POLLARD_RHO_ITERATIONS;
+use crate::factor::file_handler::{choose_primes, chosen_primes, largest_chosen_prime};
+use crate::factor::large_prime::{large_prime_bound, large_primes, LARGE_PRIMES};
+use crate::factor::pollard_rho::find_factor_by_pollard_rho;
+use crate::factor::relation_store::RelationStore;
+use crate::integers::prime::baillie_psw_is_prime;
+use crate::random::randint_below;
 
 
-// Returns factors of the square.
-fn find_one_relation(n: &Integer, rng: &mut RandState) -> (Integer, Vec<(u64, u64)>) {
-    // println!("Trying to find a relation...");
-    let mut t = n.random_below_ref(rng).complete();
+// A relation t^2 = -prod p^e (mod n) keeps the sign as the factor MINUS_ONE with exponent 1, so it
+// takes part in the linear algebra like a prime, and drops out of the square root.
+pub const MINUS_ONE: u64 = 1;
 
-    let t_factors = loop {
-        if let Some(temp_factors) = trial_division(&t.clone().pow_mod(&Integer::from(2), n).expect("Square exists.")) {
-            // break temp_factors.iter().filter(|(_, exponent)| exponent % 2 == 1).map(|(factor, _)| *factor).collect_vec()
-            break temp_factors;
-        }
-        // println!("For some reason trial division does not give factors.");
-        t = n.random_below_ref(rng).complete();
-    };
 
-    (t, t_factors)
+pub fn find_multiple_relations(n: &Integer, m: usize) -> HashMap<Integer, Vec<(u64, u64)>> {
+    find_multiple_relations_with_large_primes(n, m, LARGE_PRIMES)
 }
 
 
-pub fn find_multiple_relations(n: &Integer, m: usize) -> HashMap<Integer, Vec<(u64, u64)>> {
-    let mut rng = RandState::new();
-    rng.seed(&Integer::from(SystemTime::now().duration_since(UNIX_EPOCH).expect("We are not time travelling").as_secs()));
-    let mut hashmap: HashMap<Integer, Vec<(u64, u64)>> = HashMap::with_capacity(m);
-    // println!("Starting to find relations...");
-    for _ in 0..m {
-        // NOTE: might give the same integer and factors twice, however this is unlikely when n is
-        // large.
-        let (t, factors) = find_one_relation(n, &mut rng);
-        hashmap.insert(t, factors);
-    }
-    hashmap
+// m distinct relations from random t whose t^2 mod n is smooth apart from at most
+// `max_large_primes` primes below large_prime_bound, where 0 only keeps full relations. As t = 0
+// never gives a relation, at most n - 1 are asked for.
+pub fn find_multiple_relations_with_large_primes(n: &Integer, m: usize, max_large_primes: usize) -> HashMap<Integer, Vec<(u64, u64)>> {
+    let store = RelationStore::new(n, n.to_usize().map_or(m, |n| m.min(n - 1)));
+    find_relations_into_store(n, &store, max_large_primes);
+    store.into_relations()
+}
+
+
+// Every worker of the rayon pool tries its own random t until the store has its target of
+// relations. The progress can be followed from another thread through the store meanwhile.
+pub fn find_relations_into_store(n: &Integer, store: &RelationStore, max_large_primes: usize) {
+    let largest = largest_chosen_prime();
+    let bound = large_prime_bound(largest);
+    (0..u64::MAX).into_par_iter()
+        .find_any(|_| {
+            let t = randint_below(n);
+            let square = t.square_ref().complete().modulo(n);
+            if square == 0 {
+                return store.is_done();
+            }
+            let (factors, cofactor) = trial_division_with_cofactor(&square);
+            if let Some(large) = large_primes(&cofactor, largest, bound, max_large_primes) {
+                store.add(t, factors, large);
+            }
+            store.is_done()
+        });
 }
 
 
@@ -54,131 +68,95 @@ where
 }
 
 
-fn find_first_non_pivot(matrix: &Vec<Vec<Z2>>) -> Option<usize> {
-    for (i, row) in matrix.iter().enumerate() {
-        if !row[i].0 {
-            return Some(i);
+// All dependencies modulo 2 between the relations, that is sets of t whose t^2 multiply to a
+// square. Each row holds the exponents modulo 2 of one relation followed by a bit for every
+// relation added to it, so a row whose exponents vanish after elimination gives a dependency.
+pub fn find_all_squares_by_relations(relations: &HashMap<Integer, Vec<(u64, u64)>>) -> Vec<Vec<&Integer>> {
+    let integers = relations.keys().collect_vec();
+    let mut columns: HashMap<u64, usize> = HashMap::new();
+    for (prime, exp) in relations.values().flatten() {
+        if exp % 2 == 1 {
+            let next = columns.len();
+            columns.entry(*prime).or_insert(next);
         }
     }
-    if matrix.len() < matrix[0].len() {
-        Some(matrix.len())
-    } else {
-        None
-    }
-}
 
-
-fn find_all_pivots(matrix: &Vec<Vec<Z2>>) -> Vec<bool> {
-    let cols = matrix[0].len();
-    let rows = matrix.len();
-    let mut pivots: Vec<bool> = vec![false; cols];
-    // let mut count = 0;
-    for i in 0..rows {
-        for j in 0..cols {
-            if matrix[i][j].0 {
-                pivots[j] = true;
-                break
+    let words = (columns.len() + integers.len()).div_ceil(64);
+    let mut rows: Vec<Vec<u64>> = Vec::with_capacity(integers.len());
+    for (i, integer) in integers.iter().enumerate() {
+        let mut row = vec![0; words];
+        for (prime, exp) in relations.get(*integer).expect("Key should exist.") {
+            if exp % 2 == 1 {
+                let column = columns[prime];
+                row[column/64] ^= 1 << (column % 64);
             }
         }
+        let history = columns.len() + i;
+        row[history/64] |= 1 << (history % 64);
+        rows.push(row);
     }
-    pivots
-}
-
-
-fn find_a_nonzero_solution(matrix: &Vec<Vec<Z2>>, size_of_solution: usize) -> Option<Vec<Z2>> {
-    let mut solution = vec![Z2::zero(); size_of_solution];
-    let pivots = find_all_pivots(matrix);
-
-    let first_non_pivot = pivots.iter().position(|x| !x).unwrap_or(matrix.len());
-
-    if first_non_pivot >= size_of_solution {
-        return None;
-    }
-
-    solution[first_non_pivot] = Z2::one();
-    let mut ones: Vec<usize> = vec![first_non_pivot];
 
-    for i in (0..matrix.len()).rev() {
-        let mut sum = false;
-        for j in &ones {
-            if matrix[i][*j].0 {
-                sum ^= true;
+    // A column without a pivot among the unused rows never gets one, as rows are only added to
+    // rows which are still unused, so the unused rows end up with no exponents left.
+    let mut used = vec![false; rows.len()];
+    for column in 0..columns.len() {
+        let (word, bit) = (column/64, 1 << (column % 64));
+        let Some(pivot) = (0..rows.len()).find(|i| !used[*i] && rows[*i][word] & bit != 0) else {
+            continue;
+        };
+        used[pivot] = true;
+        let pivot_row = rows[pivot][word..].to_vec();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if !used[i] && row[word] & bit != 0 {
+                row[word..].iter_mut().zip(pivot_row.iter()).for_each(|(a, b)| *a ^= b);
             }
         }
-        if sum {
-            solution[i] = Z2::one();
-            ones.push(i);
-        }
     }
 
-    Some(solution)
-
-    // for i in 0..*pivots.last().expect("Pivot vector is non-empty.") {
-    //     if i >= matrix.len() {
-    //         break;
-    //     }
-    //     let mut count = false;
-    //     for pivot in pivots {
-    //         if matrix[i][*pivot].0 {
-    //             count ^= true;
-    //         }
-    //     }
-    //     if count {
-    //         vector[i] = Z2::one();
-    //     }
-    // }
-    // for pivot in pivots {
-    //     vector[*pivot] = Z2::one();
-    // }
-    // Some(vector)
+    rows.iter()
+        .zip(used)
+        .filter(|(_, used)| !used)
+        .map(|(row, _)| (0..integers.len())
+            .filter(|i| (row[(columns.len() + i)/64] >> ((columns.len() + i) % 64)) & 1 == 1)
+            .map(|i| integers[i])
+            .collect_vec())
+        .collect()
 }
 
+
 pub fn find_squares_by_relations(relations: &HashMap<Integer, Vec<(u64, u64)>>) -> Option<Vec<&Integer>> {
-    let integers = relations.keys().collect_vec();
-    let primes: Vec<u64> = relations.values().into_iter().flatten().filter(|(_, exp)| exp % 2 == 1 ).map(|(n, _)| *n as u64).unique().collect_vec();
-    let mut cols = integers.len();
-    let rows = primes.len();
+    find_all_squares_by_relations(relations).into_iter().next()
+}
 
-    if primes.len() > integers.len() {
-        cols = primes.len();
-    }
-    
-    let mut choice_matrix: Vec<Vec<Z2>> = vec![vec![Z2::zero(); cols]; rows];
-    for (i, integer) in integers.iter().enumerate() {
-        for prime in relations.get(integer).expect("Key should exist.") {
-            if prime.1 % 2 == 1 {
-                if let Some(index) = primes.iter().position(|&p| p == prime.0) {
-                    choice_matrix[index][i] = Z2::one();
-                }
-            }
-        }
-    }
 
-    gauss_elimination_generic(&mut choice_matrix, gauss_jordan_elimination::GaussEliminationOption::PrepareReduce);
+// gcd(x - y, n) where x is the product of the t in `square` and y is the square root of the
+// product of their t^2, known from the factorizations.
+pub fn factor_from_square(n: &Integer, relations: &HashMap<Integer, Vec<(u64, u64)>>, square: &[&Integer]) -> Integer {
+    let mut square1 = Integer::ONE.clone();
+    let mut square2_vec: Vec<(u64, u64)> = Vec::new();
+    for factor in square {
+        square1 = (square1 * *factor) % n;
+        square2_vec.extend(relations.get(*factor).expect("Key exists."));
+    }
 
-    if let Some(choice_vector) = find_a_nonzero_solution(&choice_matrix, integers.len()) {
-        let mut relation_vector = Vec::new();
-        for (i, choice) in choice_vector.iter().enumerate() {
-            if i >= integers.len() {
-                break;
-            }
-            if choice.0 {
-                relation_vector.push(integers[i]);           
-            }
-        }
-        Some(relation_vector)
-    } else {
-        None
+    let mut square2 = Integer::ONE.clone();
+    for (prime, exp) in merge_tuples(&square2_vec) {
+        square2 *= Integer::from(prime).pow_mod(&Integer::from(exp/2), n).expect("The exponent is positive");
+        square2 %= n;
     }
+
+    // println!("Found: {}^2 = {}^2  (mod {})", &square1, &square2, &n);
+    n.clone().gcd(&(&square2 - &square1).complete())
 }
 
 
 pub fn find_factors_by_random_squares(n: &Integer, number_of_relations: usize) -> Integer {
     let mut relations = find_multiple_relations(&n, number_of_relations);
-    let square_vec = loop {
+    let squares = loop {
         // println!("Trying to find squares from relations.");
-        if let Some(square) = find_squares_by_relations(&relations) {
-            break square;
+        let squares = find_all_squares_by_relations(&relations);
+        if !squares.is_empty() {
+            break squares;
         }
         relations = find_multiple_relations(&n, number_of_relations);
     };
@@ -186,41 +164,22 @@ pub fn find_factors_by_random_squares(n: &Integer, number_of_relations: usize) -
     for integer in relations.keys() {
         let mut product = Integer::ONE.clone();
         for factor in relations.get(integer).expect("Exists.") {
-            product = product * factor.0.pow(factor.1 as u32);
+            product *= Integer::u_pow_u(factor.0 as u32, factor.1 as u32).complete();
         }
-        assert_eq!(integer.clone().pow_mod(&Integer::from(2), &n).expect("Square exists."), product, "Failed checking {}, which then doesn't factor to {:#?}", integer, relations.get(integer).expect("Exists"))
+        // Relations combined from partial ones are only congruences, as the product of their
+        // right hand sides is larger than n.
+        assert_eq!(integer.clone().pow_mod(&Integer::from(2), n).expect("Square exists."), product.modulo(n), "Failed checking {}, which then doesn't factor to {:#?}", integer, relations.get(integer).expect("Exists"))
     }
 
-    let mut square1 = Integer::ONE.clone();
-    let mut square2_vec: Vec<(u64, u64)> = Vec::new();
-    for factor in square_vec {
-        square1 = square1.clone() * factor;
-        square1 = square1.clone() % n;
-
-        for prime_exp in relations.get(factor).expect("Key exists.") {
-            square2_vec.push(*prime_exp);
+    // Each dependency gives a proper factor with probability at least 1/2.
+    let mut factor = n.clone();
+    for square in squares {
+        factor = factor_from_square(n, &relations, &square);
+        if factor != 1 && factor != *n {
+            break;
         }
     }
-
-    square2_vec = merge_tuples(&square2_vec);
-
-    let mut square2 = Integer::ONE.clone();
-    for (prime, exp) in &square2_vec {
-        square2 = square2 * Integer::from(*prime).pow_mod(&Integer::from(*exp), &n).expect("Square exists.");
-        square2 = square2 % n;
-    }
-
-    square2_vec = square2_vec.iter().map(|(prime, exp)| (*prime, exp/2)).collect();
-
-    let mut square2 = Integer::ONE.clone();
-
-    for (prime, exp) in square2_vec {
-        square2 = square2 * prime.pow(exp as u32);
-        square2 = square2 % n;
-    }
-
-    // println!("Found: {}^2 = {}^2  (mod {})", &square1, &square2, &n);
-    n.clone().gcd(&(&square2 - &square1).complete())
+    factor
 }
 
 
@@ -236,19 +195,25 @@ pub fn find_two_real_factors_by_random_squares(n: &Integer, number_of_relations:
 }
 
 
+// Unlike the sieves, which only meet t^2 - kn and can use the FactorBase of primes with kn a
+// square modulo p, the values t^2 mod n = t^2 - jn come with every j, so any prime can divide them
+// and all the chosen primes are kept.
 fn trial_division(t: &Integer) -> Option<Vec<(u64, u64)>> {
-    let chosen_primes_path = "chosen-primes";
-    if !fs::exists(chosen_primes_path).expect("Can't check existence of file.") {
-        panic!("There must exist a generated set of small-primes");
+    let (factors, cofactor) = trial_division_with_cofactor(t);
+    if cofactor == 1 {
+        Some(factors)
+    } else {
+        None
     }
+}
 
+
+// The factors of t among the chosen primes and what is left of t after dividing them out.
+fn trial_division_with_cofactor(t: &Integer) -> (Vec<(u64, u64)>, Integer) {
     let mut t_clone = t.clone();
     let mut factors: Vec<(u64, u64)> = Vec::new();
 
-    let chosen_primes = fs::File::open(chosen_primes_path).expect("small-primes file should have been generated");
-    let reader = BufReader::new(chosen_primes);
-    for prime in reader.lines() {
-        let p = prime.expect("Line should exist").parse::<u64>().expect("Should be a positive integer");
+    for p in chosen_primes().iter() {
         while (&t_clone%p).complete() == 0 {
             if factors.len() == 0 {
                 factors.push((p, 1));
@@ -260,18 +225,14 @@ fn trial_division(t: &Integer) -> Option<Vec<(u64, u64)>> {
             t_clone = t_clone/p;
         }
     }
-    
-    if t_clone == 1 {
-        Some(factors)
-    } else {
-        None
-    }
+
+    (factors, t_clone)
 }
 
 
 pub fn factorization_by_random_squares(n: &Integer, number_of_relations: usize, depth: usize) -> Vec<Integer> {
     println!("Entered depth {} and we are now factorizing {}", depth, n);
-    if n.is_probably_prime(30) != IsPrime::No {
+    if baillie_psw_is_prime(n) {
         return vec![n.clone()];
     }
     if let Some(trial_division_factors) = trial_division(n) {
@@ -282,7 +243,15 @@ pub fn factorization_by_random_squares(n: &Integer, number_of_relations: usize,
         return factors;
     }
 
-    let (factor1, factor2) = find_two_real_factors_by_random_squares(n, number_of_relations);
+    // Random squares needs about as many relations for a small factor as for a large one, while
+    // Pollard's rho finds small factors quickly.
+    let (factor1, factor2) = match find_factor_by_pollard_rho(n, POLLARD_RHO_ITERATIONS) {
+        Some(factor) => {
+            let cofactor = (n / &factor).complete();
+            (factor, cofactor)
+        },
+        None => find_two_real_factors_by_random_squares(n, number_of_relations),
+    };
 
     let mut factors1 = factorization_by_random_squares(&factor1, number_of_relations, depth+1);
     let mut factors2 = factorization_by_random_squares(&factor2, number_of_relations, depth+1);
@@ -292,13 +261,87 @@ pub fn factorization_by_random_squares(n: &Integer, number_of_relations: usize,
 }
 
 
+// As factorization_by_random_squares, saving the job to `path` after every split and every
+// `interval` while relations are collected. The composites left to split are a stack, so the
+// saved relations are always those of the last one.
+pub fn factorization_by_random_squares_with_checkpoint(n: &Integer, number_of_relations: usize, path: &Path, interval: Duration) -> Vec<Integer> {
+    let method = Method::RandomSquares { chosen_primes_bound: largest_chosen_prime() };
+    let job = Checkpoint::new(n, method, LARGE_PRIMES, number_of_relations);
+    job.write_or_report(path);
+    run_random_squares_job(job, path, interval)
+}
+
+
+// Goes on with the job saved at `path` by factorization_by_random_squares_with_checkpoint, with the
+// same chosen primes and the relations it had.
+pub fn resume_factorization_by_random_squares(path: &Path, interval: Duration) -> Result<Vec<Integer>, String> {
+    let job = Checkpoint::read(path)?;
+    let Method::RandomSquares { chosen_primes_bound } = job.method else {
+        return Err(format!("{} is not a job of random squares", path.display()));
+    };
+    choose_primes(chosen_primes_bound as usize);
+    Ok(run_random_squares_job(job, path, interval))
+}
+
+
+fn run_random_squares_job(mut job: Checkpoint, path: &Path, interval: Duration) -> Vec<Integer> {
+    while let Some(m) = job.remaining.last().cloned() {
+        // Relations are only saved once rho has failed on m, so a job resumed with saved relations
+        // goes on collecting them without trying rho again.
+        let resumed = !job.relations.is_empty() || !job.partials.is_empty();
+        let mut split: Vec<Integer> = if baillie_psw_is_prime(&m) {
+            job.factors.push(m);
+            Vec::new()
+        } else if let Some(factors) = trial_division(&m) {
+            for (factor, exp) in factors {
+                job.factors.append(&mut vec![Integer::from(factor); exp as usize]);
+            }
+            Vec::new()
+        } else if let Some(factor) = (!resumed).then(|| find_factor_by_pollard_rho(&m, POLLARD_RHO_ITERATIONS)).flatten() {
+            vec![(&m/&factor).complete(), factor]
+        } else {
+            let store = RelationStore::from_checkpoint(&job, path, Some(interval));
+            find_relations_into_store(&m, &store, job.max_large_primes);
+            job = store.to_checkpoint(&job);
+            job.write_or_report(path);
+
+            let relations = store.into_relations();
+            let factor = find_all_squares_by_relations(&relations)
+                .iter()
+                .map(|square| factor_from_square(&m, &relations, square))
+                .find(|factor| *factor != 1 && *factor != m);
+            match factor {
+                Some(factor) => vec![(&m/&factor).complete(), factor],
+                // Every dependency was trivial, so the relations are collected again from scratch.
+                None => {
+                    job.relations.clear();
+                    job.partials.clear();
+                    continue;
+                },
+            }
+        };
+        job.remaining.pop();
+        job.remaining.append(&mut split);
+        job.relations.clear();
+        job.partials.clear();
+        job.write_or_report(path);
+    }
+    job.factors
+}
+
+
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, fs::File, hash::RandomState, os::unix::thread, process::{Command, Output}};
+    use std::{collections::HashSet, hash::RandomState, os::unix::thread, process::{Command, Output}};
     use crate::factor::file_handler::largest_chosen_prime;
+    use crate::factor::test_helpers::check_relation;
+    use crate::integers::integer_computations::sqrt_mod_u64;
+    use crate::integers::prime::find_prime_with_bit_length;
+    use crate::integers::sieve::PrimeTable;
 
     use rand::{thread_rng, Rng};
     use rug::integer::IsPrime;
+    use rug::rand::RandState;
 
     use super::*;
 
@@ -352,6 +395,23 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_find_multiple_relations() {
+        // Small enough for the same t to come up again and again.
+        let n = Integer::from(1009*1013);
+        for max_large_primes in [0, 2] {
+            let relations = find_multiple_relations_with_large_primes(&n, 300, max_large_primes);
+            assert_eq!(relations.len(), 300);
+            for (t, factors) in relations.iter() {
+                check_relation(&n, t, factors);
+            }
+        }
+
+        // Only 6 values of t.
+        assert_eq!(find_multiple_relations(&Integer::from(7), 20).len(), 6);
+    }
+
+
     #[test]
     fn test_find_square_from_relations() {
         let mut relations: HashMap<Integer, Vec<(u64, u64)>> = HashMap::new();
@@ -422,14 +482,9 @@ mod tests {
 
     fn get_random_prime(max: usize) -> u64 {
         let mut rng = thread_rng();
-        let filepath = "small-primes";
-        let file = File::open(filepath).expect("File should exist.");
-        let reader = BufReader::new(file);
-        let lines: Vec<u64> = reader.lines().collect_vec().iter().map(|x| x.as_ref().expect("Every line should exist.").parse::<u64>().expect("Every line should be an integer.")).collect_vec();
-        // let number_of_lines = lines.len();
-        let line_number = rng.gen_range(1..max);
-        let prime: u64 = lines[line_number];
-        prime
+        let primes = PrimeTable::first(max);
+        let index = rng.gen_range(1..max);
+        primes.as_slice()[index]
     }
 
 
@@ -458,6 +513,96 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_factorization_by_random_squares_with_checkpoint() {
+        let path = std::env::temp_dir().join(format!("random-squares-test-{}", std::process::id()));
+        let interval = Duration::from_millis(10);
+        let p = find_prime_with_bit_length(20, 30);
+        let q = find_prime_with_bit_length(20, 30);
+        let r = find_prime_with_bit_length(20, 30);
+        let mut expected = vec![Integer::from(2), Integer::from(3), p.clone(), q.clone(), r.clone()];
+        expected.sort();
+
+        // A job killed after splitting off 2 and 3, with r and pq left.
+        let n = (&p*&q).complete()*&r*6u32;
+        let mut job = Checkpoint::new(&n, Method::RandomSquares { chosen_primes_bound: 499 }, LARGE_PRIMES, 105);
+        job.factors = vec![Integer::from(2), Integer::from(3)];
+        job.remaining = vec![r.clone(), (&p*&q).complete()];
+        job.write(&path).unwrap();
+        let mut factors = resume_factorization_by_random_squares(&path, interval).unwrap();
+        factors.sort();
+        assert_eq!(factors, expected);
+        assert!(Checkpoint::read(&path).unwrap().remaining.is_empty());
+
+        let mut factors = factorization_by_random_squares_with_checkpoint(&n, 105, &path, interval);
+        factors.sort();
+        assert_eq!(factors, expected);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+
+    // A relation t^2 = s (mod pq) with t put together from the square roots of s modulo p and q,
+    // either of which is negated at random.
+    fn relation_by_crt(s: u64, p: u64, q: u64) -> Option<Integer> {
+        let mut rng = thread_rng();
+        let (mut t_p, mut t_q) = (sqrt_mod_u64(s, p)?, sqrt_mod_u64(s, q)?);
+        if rng.gen_bool(0.5) {
+            t_p = p - t_p;
+        }
+        if rng.gen_bool(0.5) {
+            t_q = q - t_q;
+        }
+        let (p, q) = (Integer::from(p), Integer::from(q));
+        let inverse = p.clone().invert(&q).expect("p and q are distinct primes");
+        Some(((Integer::from(t_q) - t_p)*inverse).modulo(&q)*&p + t_p)
+    }
+
+
+    #[test]
+    fn test_resuming_random_squares_from_saved_relations() {
+        let path = std::env::temp_dir().join(format!("random-squares-relations-test-{}", std::process::id()));
+        let mut rng = thread_rng();
+        let p = find_prime_with_bit_length(50, 30).to_u64().unwrap();
+        let q = find_prime_with_bit_length(50, 30).to_u64().unwrap();
+        let n = Integer::from(p)*q;
+        let primes = chosen_primes();
+        let primes = primes.as_slice();
+        let large_primes = PrimeTable::up_to(1000).iter().filter(|p| *p > largest_chosen_prime()).take(10).collect_vec();
+
+        // The relations of a job killed while collecting them for pq, which is too large for rho
+        // and for finding that many relations over the chosen primes afresh. A product of six
+        // chosen primes and a large prime stays below 2^64.
+        let target = 120;
+        let mut job = Checkpoint::new(&n, Method::RandomSquares { chosen_primes_bound: 499 }, LARGE_PRIMES, target);
+        while job.relations.len() < target || job.partials.len() < large_primes.len() {
+            let mut factors = (0..6).map(|_| (primes[rng.gen_range(0..primes.len())], 1)).collect_vec();
+            let large = if job.relations.len() < target { 1 } else { large_primes[job.partials.len()] };
+            factors = merge_tuples(&factors);
+            factors.sort();
+            let s = factors.iter().fold(large, |s, (prime, exp)| s*prime.pow(*exp as u32));
+            let Some(t) = relation_by_crt(s, p, q) else {
+                continue;
+            };
+            if large == 1 {
+                job.relations.push((t, factors));
+            } else {
+                job.partials.push((t, factors, (large, 1)));
+            }
+        }
+        let progress = RelationStore::from_checkpoint(&job, &path, None).progress();
+        assert_eq!((progress.relations, progress.partials), (target, 10));
+
+        // The saved relations are enough, so the job ends without collecting any more.
+        job.write(&path).unwrap();
+        let mut factors = resume_factorization_by_random_squares(&path, Duration::from_millis(10)).unwrap();
+        factors.sort();
+        assert_eq!(factors, vec![Integer::from(p.min(q)), Integer::from(p.max(q))]);
+        let job = Checkpoint::read(&path).unwrap();
+        assert!(job.remaining.is_empty() && job.relations.is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+
     #[test]
     fn test_merge_tuples() {
         let vec = vec![(1, 2), (1, 5), (5, 2), (7, 2), (5, 8), (1, 3), (7, 7), (5, 0)];
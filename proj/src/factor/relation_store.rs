@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use rug::Integer;
+use crate::factor::checkpoint::Checkpoint;
+use crate::factor::large_prime::PartialRelations;
+
+
+// A snapshot of how far the collection of relations has come.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelationProgress {
+    // Distinct full relations, including those combined from partial ones.
+    pub relations: usize,
+    pub combined: usize,
+    pub partials: usize,
+    // Relations which were found again and not counted.
+    pub duplicates: usize,
+    pub target: usize,
+}
+
+
+// Where and how often the relations are saved, with the rest of the state of the job.
+#[derive(Debug)]
+struct CheckpointSchedule {
+    path: PathBuf,
+    interval: Duration,
+    last: Mutex<Instant>,
+    job: Checkpoint,
+}
+
+
+// The relations for one n, shared by all the threads collecting them. Full relations are kept by
+// t, so a t found twice only counts once, and partial relations go to the graph of large primes
+// until they close a cycle. The counts are atomic, so another thread can follow the progress
+// while the relations are collected.
+#[derive(Debug)]
+pub struct RelationStore {
+    n: Integer,
+    target: usize,
+    relations: Mutex<HashMap<Integer, Vec<(u64, u64)>>>,
+    partials: Mutex<PartialRelations>,
+    count: AtomicUsize,
+    combined: AtomicUsize,
+    partial_count: AtomicUsize,
+    duplicates: AtomicUsize,
+    checkpoint: Option<CheckpointSchedule>,
+}
+
+
+impl RelationStore {
+    pub fn new(n: &Integer, target: usize) -> RelationStore {
+        RelationStore {
+            n: n.clone(),
+            target,
+            relations: Mutex::new(HashMap::with_capacity(target)),
+            partials: Mutex::new(PartialRelations::new()),
+            count: AtomicUsize::new(0),
+            combined: AtomicUsize::new(0),
+            partial_count: AtomicUsize::new(0),
+            duplicates: AtomicUsize::new(0),
+            checkpoint: None,
+        }
+    }
+
+
+    // A store for the last remaining composite of the job, with the relations saved in it. If the
+    // interval is given, the job is written to `path` with the relations found so far at most
+    // that often while relations are added.
+    pub fn from_checkpoint(job: &Checkpoint, path: &Path, interval: Option<Duration>) -> RelationStore {
+        let n = job.remaining.last().expect("The job has a composite left");
+        let mut store = RelationStore::new(n, job.target);
+        for (t, factors) in job.relations.iter() {
+            store.add(t.clone(), factors.clone(), (1, 1));
+        }
+        for (t, factors, large) in job.partials.iter() {
+            store.add(t.clone(), factors.clone(), *large);
+        }
+        store.checkpoint = interval.map(|interval| CheckpointSchedule {
+            path: path.to_path_buf(),
+            interval,
+            last: Mutex::new(Instant::now()),
+            job: job.clone(),
+        });
+        store
+    }
+
+
+    // The job with the relations of the store in place of those it had.
+    pub fn to_checkpoint(&self, job: &Checkpoint) -> Checkpoint {
+        let mut job = job.clone();
+        job.relations = self.relations.lock().expect("No thread panics holding the relations")
+            .iter()
+            .map(|(t, factors)| (t.clone(), factors.clone()))
+            .collect();
+        job.partials = self.partials.lock().expect("No thread panics holding the partial relations")
+            .iter()
+            .map(|(t, factors, large)| (t.clone(), factors.to_vec(), large))
+            .collect();
+        job
+    }
+
+
+    // Only one thread writes, the others go on if it is already doing so.
+    fn write_checkpoint_if_due(&self) {
+        let Some(schedule) = &self.checkpoint else {
+            return;
+        };
+        let Ok(mut last) = schedule.last.try_lock() else {
+            return;
+        };
+        if last.elapsed() >= schedule.interval {
+            self.to_checkpoint(&schedule.job).write_or_report(&schedule.path);
+            *last = Instant::now();
+        }
+    }
+
+
+    // Adds the relation t^2 = P_1 P_2 prod p^e (mod n), which is a full one if both large primes
+    // are 1. Returns true if this gave a new full relation.
+    pub fn add(&self, t: Integer, factors: Vec<(u64, u64)>, large: (u64, u64)) -> bool {
+        self.write_checkpoint_if_due();
+        if large != (1, 1) {
+            let combined = self.partials.lock().expect("No thread panics holding the partial relations").add(&self.n, t, factors, large);
+            return match combined {
+                Some((t, factors)) => {
+                    let new = self.insert(t, factors);
+                    if new {
+                        self.combined.fetch_add(1, Ordering::Relaxed);
+                    }
+                    new
+                },
+                None => {
+                    self.partial_count.fetch_add(1, Ordering::Relaxed);
+                    false
+                },
+            };
+        }
+        self.insert(t, factors)
+    }
+
+
+    fn insert(&self, t: Integer, factors: Vec<(u64, u64)>) -> bool {
+        let mut relations = self.relations.lock().expect("No thread panics holding the relations");
+        if relations.contains_key(&t) {
+            self.duplicates.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        relations.insert(t, factors);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+
+    pub fn len(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+
+    pub fn is_done(&self) -> bool {
+        self.len() >= self.target
+    }
+
+
+    pub fn progress(&self) -> RelationProgress {
+        RelationProgress {
+            relations: self.len(),
+            combined: self.combined.load(Ordering::Relaxed),
+            partials: self.partial_count.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            target: self.target,
+        }
+    }
+
+
+    pub fn into_relations(self) -> HashMap<Integer, Vec<(u64, u64)>> {
+        self.relations.into_inner().expect("No thread panics holding the relations")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_relation_store() {
+        let n = Integer::from(1000003*1000033u64);
+        let store = RelationStore::new(&n, 3);
+        assert!(store.is_empty());
+
+        assert!(store.add(Integer::from(5), vec![(2, 1)], (1, 1)));
+        assert!(!store.add(Integer::from(5), vec![(2, 1)], (1, 1)));
+        assert!(!store.add(Integer::from(7), vec![(3, 1)], (101, 1)));
+        assert!(store.add(Integer::from(11), vec![(5, 1)], (101, 1)));
+        assert_eq!(store.progress(), RelationProgress { relations: 2, combined: 1, partials: 1, duplicates: 1, target: 3 });
+        assert!(!store.is_done());
+
+        // Followed from another thread while two threads add relations.
+        thread::scope(|scope| {
+            for start in [0, 1] {
+                let store = &store;
+                scope.spawn(move || {
+                    for t in (100 + start..200).step_by(2) {
+                        store.add(Integer::from(t), vec![(2, 1)], (1, 1));
+                    }
+                });
+            }
+            let mut last = 0;
+            while last < 102 {
+                let progress = store.progress();
+                assert!(progress.relations >= last);
+                last = progress.relations;
+            }
+        });
+        assert!(store.is_done());
+        let relations = store.into_relations();
+        assert_eq!(relations.len(), 102);
+        assert_eq!(relations[&Integer::from(77)], vec![(3, 1), (5, 1), (101, 2)]);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::factor::fermat::gcd;
+
+
+// SQUFOF is meant for n below this, where it beats the general methods. The P_i and Q_i below are
+// bounded by 2 sqrt(kn) < 2^37, and everything is done in i128 so that kn fits for every multiplier.
+pub const SQUFOF_BOUND: u64 = 1 << 62;
+
+// Squarefree products of 3, 5, 7 and 11, as Gower and Wagstaff suggest.
+const MULTIPLIERS: [u64; 16] = [1, 3, 5, 7, 11, 15, 21, 33, 35, 55, 77, 105, 165, 231, 385, 1155];
+
+
+// Shanks' square forms factorization with multiplier k. It walks the continued fraction expansion
+// of sqrt(kn), with P_i and Q_i as in the continued fraction method, until a Q_i with i even is a
+// square r^2. The reduced form (r, P_i, ...) is then taken backwards along the same recurrence
+// until P stops changing, where the current Q shares a factor with n. Both walks take
+// O(n^(1/4)) steps.
+fn squfof_with_multiplier(n: u64, k: u64) -> Option<u64> {
+    let kn = k as i128*n as i128;
+    let p0 = kn.isqrt();
+    if p0*p0 == kn {
+        return None;
+    }
+
+    let (mut p, mut p_previous, mut q, mut q_previous) = (p0, p0, kn - p0*p0, 1);
+    let bound = 6*(2.0*(kn as f64).sqrt()).sqrt() as usize;
+    let mut root = 0;
+    for i in 2..bound {
+        let b = (p0 + p)/q;
+        p = b*q - p;
+        let q_current = q;
+        q = q_previous + b*(p_previous - p);
+        let r = q.isqrt();
+        if i % 2 == 0 && r*r == q {
+            root = r;
+            break;
+        }
+        q_previous = q_current;
+        p_previous = p;
+    }
+    if root == 0 {
+        return None;
+    }
+
+    let b = (p0 - p)/root;
+    p += b*root;
+    q_previous = root;
+    q = (kn - p*p)/q_previous;
+    loop {
+        let b = (p0 + p)/q;
+        let p_previous = p;
+        p = b*q - p;
+        let q_current = q;
+        q = q_previous + b*(p_previous - p);
+        q_previous = q_current;
+        if p == p_previous {
+            break;
+        }
+    }
+
+    let factor = gcd(n, q_previous as u64);
+    if factor != 1 && factor != n {
+        Some(factor)
+    } else {
+        None
+    }
+}
+
+
+// A proper factor of the odd composite n < 2^62 by SQUFOF, trying the multipliers in turn, or None
+// if all fail. Runs on native integers only.
+pub fn squfof(n: u64) -> Option<u64> {
+    if n >= SQUFOF_BOUND {
+        panic!("SQUFOF is only implemented for n < 2^62");
+    }
+    if n < 4 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let root = n.isqrt();
+    if root*root == n {
+        return Some(root);
+    }
+
+    MULTIPLIERS.iter().find_map(|k| squfof_with_multiplier(n, *k).map(|factor| {
+        // The factor may be one of k rather than of n.
+        gcd(factor, n)
+    }).filter(|factor| *factor != 1 && *factor != n))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::integers::prime::find_prime_with_bit_length;
+
+    use super::*;
+
+    #[test]
+    fn test_squfof() {
+        // The example of Gower and Wagstaff.
+        assert!(matches!(squfof(11111), Some(41) | Some(271)));
+
+        let loops = 20;
+        for bits in [20, 32, 44, 56, 62] {
+            for _ in 0..loops {
+                let p = find_prime_with_bit_length(bits/2 - 1, 30).to_u64().unwrap();
+                let q = find_prime_with_bit_length(bits/2, 30).to_u64().unwrap();
+                let factor = squfof(p*q).unwrap();
+                assert!(factor == p || factor == q, "Found {} as a factor of {}", factor, p*q);
+            }
+        }
+
+        let p = find_prime_with_bit_length(20, 30).to_u64().unwrap();
+        assert_eq!(squfof(p*p), Some(p));
+        assert_eq!(squfof(1000003), None);
+    }
+}
@@ -0,0 +1,42 @@
+use rand::{thread_rng, Rng};
+use rug::{Complete, Integer};
+use crate::factor::pollard_p_minus_one::SmoothnessResult;
+use crate::factor::random_squares::MINUS_ONE;
+use crate::integers::prime::baillie_psw_is_prime;
+use crate::integers::sieve::PrimeTable;
+
+
+// Checks that t^2 = prod p^e (mod n), with MINUS_ONE standing for the sign.
+pub(crate) fn check_relation(n: &Integer, t: &Integer, factors: &[(u64, u64)]) {
+    let mut product = Integer::ONE.clone();
+    for (p, exp) in factors {
+        product *= if *p == MINUS_ONE { Integer::from(if exp % 2 == 1 { -1 } else { 1 }) } else { Integer::from(Integer::u_pow_u(*p as u32, *exp as u32)) };
+    }
+    assert!((t.square_ref().complete() - product).is_divisible(n), "{} does not give the relation {:?}", t, factors);
+}
+
+
+// A prime p of at least `bits` bits with p + sign = 2*(distinct odd primes below b1)*large for
+// sign = +-1.
+pub(crate) fn find_prime_with_smooth_neighbour(bits: u32, b1: u64, large: u64, sign: i32) -> Integer {
+    let primes = PrimeTable::up_to(b1);
+    let mut rng = thread_rng();
+    loop {
+        let mut m = Integer::from(2*large);
+        while m.significant_bits() < bits {
+            let q = primes.as_slice()[rng.gen_range(1..primes.len())];
+            if !m.is_divisible_u(q as u32) {
+                m *= q;
+            }
+        }
+        let p: Integer = m - sign;
+        if baillie_psw_is_prime(&p) {
+            return p;
+        }
+    }
+}
+
+
+pub(crate) fn assert_factor(result: SmoothnessResult, p: &Integer) {
+    assert_eq!(result, SmoothnessResult::Factor(p.clone()));
+}
@@ -0,0 +1,141 @@
+use rug::{Complete, Integer};
+use crate::factor::pollard_p_minus_one::{check_gcd, largest_power_below, SmoothnessResult, GCD_INTERVAL};
+use crate::integers::prime::lucas_sequence;
+use crate::integers::sieve::PrimeTable;
+use crate::random::randint_below;
+
+
+// Stage 2 writes each prime q = wj +- i with |i| <= w/2.
+const GIANT_STEP: u64 = 210;
+
+
+// V_k(x) = V_k for the Lucas sequence with P = x and Q = 1, so that V_k(V_m(x)) = V_(km)(x).
+fn lucas_v(x: &Integer, k: &Integer, n: &Integer) -> Integer {
+    lucas_sequence(x, Integer::ONE, k, n).1
+}
+
+
+// Williams' p + 1 with seed A. Let D = A^2 - 4 and alpha a root of z^2 - Az + 1 in F_(p^2), so
+// that V_k(A) = alpha^k + alpha^-k. If (D/p) = -1, alpha has order dividing p + 1, and when
+// p + 1 | E = prod_{q <= B1} q^e we get V_E(A) = 2 (mod p). If (D/p) = 1, this finds p with p - 1
+// smooth instead, so several seeds should be tried. Stage 2 allows one more prime q in (B1, B2]
+// dividing p + 1, using that alpha^q = 1 if and only if V_(wj) = V_i for q = wj +- i.
+pub fn williams_p_plus_one(n: &Integer, seed: &Integer, b1: u64, b2: u64) -> SmoothnessResult {
+    if n.is_even() {
+        return SmoothnessResult::Factor(Integer::from(2));
+    }
+    let primes = PrimeTable::up_to(b1.max(b2));
+    let (stage_one, stage_two) = primes.as_slice().split_at(primes.as_slice().partition_point(|q| *q <= b1));
+
+    let mut x = seed.clone().modulo(n);
+    for chunk in stage_one.chunks(GCD_INTERVAL) {
+        let start = x.clone();
+        for q in chunk {
+            x = lucas_v(&x, &largest_power_below(*q, b1), n);
+        }
+        match check_gcd(&(&x - 2u32).complete(), n) {
+            Some(SmoothnessResult::AllFactorsAtOnce) => {
+                x = start;
+                for q in chunk {
+                    x = lucas_v(&x, &largest_power_below(*q, b1), n);
+                    if let Some(result) = check_gcd(&(&x - 2u32).complete(), n) {
+                        return result;
+                    }
+                }
+            },
+            Some(result) => return result,
+            None => (),
+        }
+    }
+
+    if stage_two.is_empty() {
+        return SmoothnessResult::BoundsExhausted;
+    }
+    // baby[i] = V_i(x) for 0 <= i <= w/2
+    let baby: Vec<Integer> = (0..=GIANT_STEP/2).map(|i| lucas_v(&x, &Integer::from(i), n)).collect();
+    let v_w = lucas_v(&x, &Integer::from(GIANT_STEP), n);
+
+    // V_(w(j+1)) = V_(wj)*V_w - V_(w(j-1))
+    let mut j = 0;
+    let mut giant = Integer::from(2).modulo(n);
+    let mut previous_giant = v_w.clone();
+    let mut product = Integer::ONE.clone();
+    for (index, q) in stage_two.iter().enumerate() {
+        while (j*GIANT_STEP + GIANT_STEP/2) < *q {
+            let next = ((&giant*&v_w).complete() - &previous_giant).modulo(n);
+            previous_giant = std::mem::replace(&mut giant, next);
+            j += 1;
+        }
+        let i = (j*GIANT_STEP).abs_diff(*q);
+        product = (product*(&giant - &baby[i as usize]).complete()).modulo(n);
+        if index % GCD_INTERVAL == 0 {
+            if let Some(result) = check_gcd(&product, n) {
+                return result;
+            }
+        }
+    }
+
+    check_gcd(&product, n).unwrap_or(SmoothnessResult::BoundsExhausted)
+}
+
+
+// Tries random seeds until a factor is found or `seeds` have been tried. Small seeds like
+// 3, 4, 5, ... are a poor choice, as the D = A^2 - 4 are then products of the same few primes and
+// (D/p) = 1 for all of them is far more likely than for independent seeds.
+pub fn williams_p_plus_one_with_seeds(n: &Integer, b1: u64, b2: u64, seeds: usize) -> SmoothnessResult {
+    let mut result = SmoothnessResult::BoundsExhausted;
+    let n_minus_six = (n - 6u32).complete().max(Integer::ONE.clone());
+    for _ in 0..seeds {
+        let seed = randint_below(&n_minus_six) + 3u32;
+        result = williams_p_plus_one(n, &seed, b1, b2);
+        if let SmoothnessResult::Factor(_) = result {
+            return result;
+        }
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::factor::pollard_p_minus_one::pollard_p_minus_one;
+    use crate::factor::test_helpers::{assert_factor, find_prime_with_smooth_neighbour};
+    use crate::integers::prime::find_strong_prime_with_bit_length;
+
+    use super::*;
+
+    // A random seed A with (D/p) = -1, so that p is found whenever p + 1 is smooth.
+    fn seed_for_p_plus_one(p: &Integer) -> Integer {
+        loop {
+            let seed = randint_below(p);
+            if (seed.square_ref().complete() - 4u32).jacobi(p) == -1 {
+                return seed;
+            }
+        }
+    }
+
+    #[test]
+    fn test_williams_p_plus_one() {
+        let loops = 5;
+        for _ in 0..loops {
+            let p = find_prime_with_smooth_neighbour(80, 1000, 1, 1);
+            let q = find_strong_prime_with_bit_length(128, None, 30, 1000);
+            let n = (&p*&q).complete();
+            assert_eq!(pollard_p_minus_one(&n, &Integer::from(2), 1000, 100000), SmoothnessResult::BoundsExhausted);
+            assert_factor(williams_p_plus_one(&n, &seed_for_p_plus_one(&p), 1000, 1000), &p);
+
+            // 50021 is prime, so p + 1 is only found in stage 2.
+            let p = find_prime_with_smooth_neighbour(80, 1000, 50021, 1);
+            let n = (&p*&q).complete();
+            let seed = seed_for_p_plus_one(&p);
+            assert_eq!(williams_p_plus_one(&n, &seed, 1000, 1000), SmoothnessResult::BoundsExhausted);
+            assert_factor(williams_p_plus_one(&n, &seed, 1000, 100000), &p);
+        }
+
+        // Each random seed has (D/p) = -1 with probability about 1/2, so 40 of them fail to find p
+        // only with probability 2^-40.
+        let p = find_prime_with_smooth_neighbour(80, 1000, 1, 1);
+        let q = find_strong_prime_with_bit_length(128, None, 30, 1000);
+        assert_factor(williams_p_plus_one_with_seeds(&(&p*&q).complete(), 1000, 1000, 40), &p);
+    }
+}
@@ -1,2 +1,11 @@
 pub mod integer_computations;
 pub mod prime;
+pub mod arithmetic_functions;
+pub mod certificate;
+pub mod sieve;
+pub mod primality_test;
+pub mod prime_statistics;
+pub mod prime_counting;
+pub mod pseudoprimes;
+pub mod special_form;
+pub mod group_parameters;
@@ -0,0 +1,154 @@
+use rug::ops::Pow;
+use rug::{Complete, Integer};
+use crate::factor::factorization::factorize;
+
+
+// All functions take either a positive integer, which is factored by the factor module, or its
+// factorization as pairs (p, e) of distinct primes and positive exponents.
+
+
+fn positive_factorization(n: &Integer) -> Vec<(Integer, u32)> {
+    if *n <= 0 {
+        panic!("Arithmetic functions are only defined for positive integers");
+    }
+    factorize(n)
+}
+
+
+pub fn euler_phi(n: &Integer) -> Integer {
+    euler_phi_from_factorization(&positive_factorization(n))
+}
+
+
+// phi(p^e) = p^(e-1) * (p-1)
+pub fn euler_phi_from_factorization(factors: &[(Integer, u32)]) -> Integer {
+    let mut phi = Integer::ONE.clone();
+    for (p, e) in factors {
+        phi *= p.clone().pow(e-1) * (p - Integer::ONE).complete();
+    }
+    phi
+}
+
+
+pub fn carmichael_lambda(n: &Integer) -> Integer {
+    carmichael_lambda_from_factorization(&positive_factorization(n))
+}
+
+
+// lambda(n) is the lcm of lambda(p^e), which equals phi(p^e) except for 2^e with e >= 3 where
+// the group is not cyclic and lambda(2^e) = 2^(e-2).
+pub fn carmichael_lambda_from_factorization(factors: &[(Integer, u32)]) -> Integer {
+    let mut lambda = Integer::ONE.clone();
+    for (p, e) in factors {
+        let lambda_pe = if *p == 2 && *e >= 3 {
+            Integer::from(1) << (e-2)
+        } else {
+            p.clone().pow(e-1) * (p - Integer::ONE).complete()
+        };
+        lambda.lcm_mut(&lambda_pe);
+    }
+    lambda
+}
+
+
+pub fn sum_of_divisors(n: &Integer) -> Integer {
+    sum_of_divisors_from_factorization(&positive_factorization(n))
+}
+
+
+// sigma(p^e) = (p^(e+1) - 1)/(p - 1)
+pub fn sum_of_divisors_from_factorization(factors: &[(Integer, u32)]) -> Integer {
+    let mut sigma = Integer::ONE.clone();
+    for (p, e) in factors {
+        sigma *= (p.clone().pow(e+1) - 1) / (p - Integer::ONE).complete();
+    }
+    sigma
+}
+
+
+pub fn number_of_divisors(n: &Integer) -> Integer {
+    number_of_divisors_from_factorization(&positive_factorization(n))
+}
+
+
+pub fn number_of_divisors_from_factorization(factors: &[(Integer, u32)]) -> Integer {
+    let mut tau = Integer::ONE.clone();
+    for (_, e) in factors {
+        tau *= e + 1;
+    }
+    tau
+}
+
+
+pub fn mobius(n: &Integer) -> i32 {
+    mobius_from_factorization(&positive_factorization(n))
+}
+
+
+pub fn mobius_from_factorization(factors: &[(Integer, u32)]) -> i32 {
+    if factors.iter().any(|(_, e)| *e > 1) {
+        return 0;
+    }
+    (-1i32).pow(factors.len() as u32)
+}
+
+
+pub fn divisors(n: &Integer) -> Divisors {
+    Divisors::new(positive_factorization(n))
+}
+
+
+pub fn divisors_from_factorization(factors: &[(Integer, u32)]) -> Divisors {
+    Divisors::new(factors.to_vec())
+}
+
+
+// Iterates over all positive divisors by counting through the exponent vectors
+// (e_1, ..., e_k) with 0 <= e_i <= the exponent of p_i. The divisors are not returned in
+// increasing order.
+#[derive(Debug, Clone)]
+pub struct Divisors {
+    factors: Vec<(Integer, u32)>,
+    exponents: Vec<u32>,
+    finished: bool,
+}
+
+
+impl Divisors {
+    fn new(factors: Vec<(Integer, u32)>) -> Divisors {
+        let exponents = vec![0; factors.len()];
+        Divisors {
+            factors,
+            exponents,
+            finished: false,
+        }
+    }
+}
+
+
+impl Iterator for Divisors {
+    type Item = Integer;
+
+    fn next(&mut self) -> Option<Integer> {
+        if self.finished {
+            return None;
+        }
+
+        let mut divisor = Integer::ONE.clone();
+        for ((p, _), e) in self.factors.iter().zip(&self.exponents) {
+            divisor *= p.clone().pow(*e);
+        }
+
+        self.finished = true;
+        for (i, (_, max_exponent)) in self.factors.iter().enumerate() {
+            if self.exponents[i] < *max_exponent {
+                self.exponents[i] += 1;
+                self.finished = false;
+                break;
+            }
+            self.exponents[i] = 0;
+        }
+
+        Some(divisor)
+    }
+}
@@ -0,0 +1,377 @@
+use rug::ops::Pow;
+use rug::{Complete, Integer};
+use std::fmt;
+use std::str::{FromStr, Lines};
+use crate::factor::factorization::factorize;
+use crate::integers::integer_computations::pow_rug;
+use crate::integers::sieve::PrimeTable;
+use crate::integers::prime::{baillie_psw_is_prime, rabin_miller_is_prime_u64, rabin_miller_is_prime_with_bases};
+use crate::random::{randint_below, randint_bits_odd};
+
+
+// p - 1 is only trial divided up to this bound when looking for a partial factorization.
+const POCKLINGTON_TRIAL_DIVISION_BOUND: u64 = 1 << 16;
+const MAX_WITNESS: u64 = 1000;
+
+
+// A proof that p is prime, where every prime used in the proof is proven recursively.
+//
+//   Small:       p < 2^64, which the deterministic Rabin-Miller test decides.
+//   Pratt:       p - 1 = q_1^e_1 * ... * q_k^e_k and the witness a has order p - 1, that is
+//                a^(p-1) = 1 and a^((p-1)/q_i) != 1 (mod p) for all i.
+//   Pocklington: F = q_1^e_1 * ... * q_k^e_k divides p - 1 and for every q_i there is a witness
+//                a_i with a_i^(p-1) = 1 (mod p) and gcd(a_i^((p-1)/q_i) - 1, p) = 1. Then every
+//                prime factor of p is 1 mod F, so p is prime if F^2 >= p. For p^(1/3) <= F the
+//                Brillhart-Lehmer-Selfridge test writes p = c_2*F^2 + c_1*F + 1 and p is prime
+//                if and only if c_1^2 - 4*c_2 is not a square.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrimalityCertificate {
+    Small(Integer),
+    Pratt {
+        p: Integer,
+        witness: Integer,
+        factors: Vec<(Integer, u32, PrimalityCertificate)>,
+    },
+    Pocklington {
+        p: Integer,
+        factors: Vec<PocklingtonFactor>,
+    },
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PocklingtonFactor {
+    pub prime: Integer,
+    pub exponent: u32,
+    pub witness: Integer,
+    pub certificate: PrimalityCertificate,
+}
+
+
+impl PrimalityCertificate {
+    pub fn prime(&self) -> &Integer {
+        match self {
+            PrimalityCertificate::Small(p) => p,
+            PrimalityCertificate::Pratt { p, .. } => p,
+            PrimalityCertificate::Pocklington { p, .. } => p,
+        }
+    }
+
+
+    // Checks the certificate without using any randomness or trusting the generator.
+    pub fn verify(&self) -> bool {
+        match self {
+            PrimalityCertificate::Small(p) => {
+                match p.to_u64() {
+                    Some(p) => rabin_miller_is_prime_u64(p),
+                    None => false,
+                }
+            },
+            PrimalityCertificate::Pratt { p, witness, factors } => {
+                if *p < 3 {
+                    return false;
+                }
+                let p_minus_one = (p - Integer::ONE).complete();
+                let mut product = Integer::ONE.clone();
+                for (q, e, certificate) in factors {
+                    if certificate.prime() != q || !certificate.verify() {
+                        return false;
+                    }
+                    product *= q.clone().pow(*e);
+                }
+                if product != p_minus_one || pow_rug(witness, &p_minus_one, p) != 1 {
+                    return false;
+                }
+                factors.iter().all(|(q, _, _)| pow_rug(witness, &(&p_minus_one / q).complete(), p) != 1)
+            },
+            PrimalityCertificate::Pocklington { p, factors } => {
+                if *p < 3 {
+                    return false;
+                }
+                let p_minus_one = (p - Integer::ONE).complete();
+                let mut f = Integer::ONE.clone();
+                for factor in factors {
+                    if factor.certificate.prime() != &factor.prime || !factor.certificate.verify() {
+                        return false;
+                    }
+                    if pow_rug(&factor.witness, &p_minus_one, p) != 1 {
+                        return false;
+                    }
+                    let a = pow_rug(&factor.witness, &(&p_minus_one / &factor.prime).complete(), p);
+                    if (a - Integer::ONE).gcd(p) != 1 {
+                        return false;
+                    }
+                    f *= factor.prime.clone().pow(factor.exponent);
+                }
+                if !p_minus_one.is_divisible(&f) {
+                    return false;
+                }
+
+                if f.clone().square() >= *p {
+                    true
+                } else if f.clone().pow(3) >= *p {
+                    let (c2, c1) = (&p_minus_one / &f).complete().div_rem(f);
+                    let discriminant: Integer = c1.square() - c2*4u32;
+                    !discriminant.is_perfect_square()
+                } else {
+                    false
+                }
+            },
+        }
+    }
+}
+
+
+// The serialized form has one node per line followed by its children, for example
+//      pratt 7 3 2
+//      2 1
+//      small 2
+//      3 1
+//      small 3
+// where the node line holds p, the witness and the number of factors, and every factor line
+// holds q, e (and the witness for Pocklington) and is followed by the certificate of q.
+impl fmt::Display for PrimalityCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrimalityCertificate::Small(p) => write!(f, "small {}", p),
+            PrimalityCertificate::Pratt { p, witness, factors } => {
+                write!(f, "pratt {} {} {}", p, witness, factors.len())?;
+                for (q, e, certificate) in factors {
+                    write!(f, "\n{} {}\n{}", q, e, certificate)?;
+                }
+                Ok(())
+            },
+            PrimalityCertificate::Pocklington { p, factors } => {
+                write!(f, "pocklington {} {}", p, factors.len())?;
+                for factor in factors {
+                    write!(f, "\n{} {} {}\n{}", factor.prime, factor.exponent, factor.witness, factor.certificate)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+
+impl FromStr for PrimalityCertificate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        let certificate = parse_certificate(&mut lines)?;
+        if lines.any(|line| !line.trim().is_empty()) {
+            return Err("Trailing lines after certificate".to_string());
+        }
+        Ok(certificate)
+    }
+}
+
+
+fn parse_integer(word: Option<&str>) -> Result<Integer, String> {
+    let word = word.ok_or("Missing integer in certificate")?;
+    Integer::from_str(word).map_err(|e| format!("Could not parse {}: {}", word, e))
+}
+
+
+fn parse_u32(word: Option<&str>) -> Result<u32, String> {
+    let word = word.ok_or("Missing exponent in certificate")?;
+    word.parse::<u32>().map_err(|e| format!("Could not parse {}: {}", word, e))
+}
+
+
+fn parse_certificate(lines: &mut Lines) -> Result<PrimalityCertificate, String> {
+    let line = lines.next().ok_or("Unexpected end of certificate")?;
+    let mut words = line.split_whitespace();
+    match words.next() {
+        Some("small") => Ok(PrimalityCertificate::Small(parse_integer(words.next())?)),
+        Some("pratt") => {
+            let p = parse_integer(words.next())?;
+            let witness = parse_integer(words.next())?;
+            let k = parse_u32(words.next())?;
+            let mut factors = Vec::new();
+            for _ in 0..k {
+                let mut words = lines.next().ok_or("Missing factor line")?.split_whitespace();
+                let q = parse_integer(words.next())?;
+                let e = parse_u32(words.next())?;
+                factors.push((q, e, parse_certificate(lines)?));
+            }
+            Ok(PrimalityCertificate::Pratt { p, witness, factors })
+        },
+        Some("pocklington") => {
+            let p = parse_integer(words.next())?;
+            let k = parse_u32(words.next())?;
+            let mut factors = Vec::new();
+            for _ in 0..k {
+                let mut words = lines.next().ok_or("Missing factor line")?.split_whitespace();
+                let prime = parse_integer(words.next())?;
+                let exponent = parse_u32(words.next())?;
+                let witness = parse_integer(words.next())?;
+                let certificate = parse_certificate(lines)?;
+                factors.push(PocklingtonFactor { prime, exponent, witness, certificate });
+            }
+            Ok(PrimalityCertificate::Pocklington { p, factors })
+        },
+        _ => Err(format!("Unknown certificate line: {}", line)),
+    }
+}
+
+
+// Tries a small certificate and then a Pocklington certificate from a partial factorization of
+// p - 1. A Pratt certificate is not tried as factoring p - 1 completely may take very long.
+pub fn certify_prime(p: &Integer) -> Option<PrimalityCertificate> {
+    if let Some(m) = p.to_u64() {
+        return if rabin_miller_is_prime_u64(m) {
+            Some(PrimalityCertificate::Small(p.clone()))
+        } else {
+            None
+        };
+    }
+    if !baillie_psw_is_prime(p) {
+        return None;
+    }
+    pocklington_certificate(p)
+}
+
+
+pub fn pratt_certificate(p: &Integer) -> Option<PrimalityCertificate> {
+    if *p < 3 {
+        return certify_prime(p);
+    }
+    let factors = factorize(&(p - Integer::ONE).complete());
+    pratt_certificate_from_factorization(p, &factors)
+}
+
+
+// `factors` has to be the full factorization of p - 1.
+pub fn pratt_certificate_from_factorization(p: &Integer, factors: &[(Integer, u32)]) -> Option<PrimalityCertificate> {
+    let p_minus_one = (p - Integer::ONE).complete();
+    let witness = (2..MAX_WITNESS).map(Integer::from).find(|a| {
+        pow_rug(a, &p_minus_one, p) == 1
+            && factors.iter().all(|(q, _)| pow_rug(a, &(&p_minus_one / q).complete(), p) != 1)
+    })?;
+
+    let mut certified_factors = Vec::with_capacity(factors.len());
+    for (q, e) in factors {
+        certified_factors.push((q.clone(), *e, certify_prime(q)?));
+    }
+
+    let certificate = PrimalityCertificate::Pratt { p: p.clone(), witness, factors: certified_factors };
+    if certificate.verify() {
+        Some(certificate)
+    } else {
+        None
+    }
+}
+
+
+pub fn pocklington_certificate(p: &Integer) -> Option<PrimalityCertificate> {
+    if *p < 3 {
+        return certify_prime(p);
+    }
+    let mut r = (p - Integer::ONE).complete();
+    let mut factors: Vec<(Integer, u32)> = Vec::new();
+
+    for q in PrimeTable::up_to(POCKLINGTON_TRIAL_DIVISION_BOUND).iter() {
+        if q*q > r {
+            break;
+        }
+        let mut exponent = 0;
+        while r.is_divisible_u(q as u32) {
+            r /= q;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((Integer::from(q), exponent));
+        }
+    }
+    if r != 1 && baillie_psw_is_prime(&r) {
+        factors.push((r, 1));
+    }
+
+    pocklington_certificate_from_partial_factorization(p, &factors)
+}
+
+
+// The primes in `factors` have to divide p - 1 to the given powers and their product F has to
+// satisfy F^3 >= p.
+pub fn pocklington_certificate_from_partial_factorization(p: &Integer, factors: &[(Integer, u32)]) -> Option<PrimalityCertificate> {
+    let p_minus_one = (p - Integer::ONE).complete();
+    let mut certified_factors = Vec::with_capacity(factors.len());
+
+    for (q, e) in factors {
+        let exponent = (&p_minus_one / q).complete();
+        let witness = (2..MAX_WITNESS).map(Integer::from).find(|a| {
+            pow_rug(a, &p_minus_one, p) == 1
+                && (pow_rug(a, &exponent, p) - Integer::ONE).gcd(p) == 1
+        })?;
+        certified_factors.push(PocklingtonFactor {
+            prime: q.clone(),
+            exponent: *e,
+            witness,
+            certificate: certify_prime(q)?,
+        });
+    }
+
+    let certificate = PrimalityCertificate::Pocklington { p: p.clone(), factors: certified_factors };
+    if certificate.verify() {
+        Some(certificate)
+    } else {
+        None
+    }
+}
+
+
+// Shawe-Taylor style recursive construction. A provable prime q of half the size is generated
+// recursively and candidates n = 2rq + 1 are searched for with r uniform such that n has exactly
+// the requested bit length. As q has at least ceil(bits/2) + 1 bits we get q^2 > n, so a single
+// Pocklington witness for q proves n prime.
+pub fn find_provable_prime_with_bit_length(bits: usize) -> (Integer, PrimalityCertificate) {
+    if bits < 2 {
+        panic!("There are no primes with less than 2 bits");
+    }
+    if bits <= 64 {
+        loop {
+            let p = randint_bits_odd(bits);
+            if rabin_miller_is_prime_u64(p.to_u64().expect("At most 64 bits")) {
+                return (p.clone(), PrimalityCertificate::Small(p));
+            }
+        }
+    }
+
+    let (q, q_certificate) = find_provable_prime_with_bit_length(bits.div_ceil(2) + 1);
+    let two_q: Integer = (&q * 2u32).complete();
+    let lower: Integer = ((Integer::ONE.clone() << (bits as u32 - 1)) - 1u32 + &two_q - 1u32) / &two_q;
+    let upper: Integer = ((Integer::ONE.clone() << bits as u32) - 2u32) / &two_q;
+    let width: Integer = (&upper - &lower).complete() + 1u32;
+
+    loop {
+        let r: Integer = randint_below(&width) + &lower;
+        let n: Integer = r * &two_q + 1u32;
+        if [3u32, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37].iter().any(|p| n.is_divisible_u(*p)) {
+            continue;
+        }
+        if !rabin_miller_is_prime_with_bases(&n, &[2]) {
+            continue;
+        }
+
+        let n_minus_one = (&n - Integer::ONE).complete();
+        let exponent = (&n_minus_one / &q).complete();
+        let witness = (2..MAX_WITNESS).map(Integer::from).find(|a| {
+            pow_rug(a, &n_minus_one, &n) == 1
+                && (pow_rug(a, &exponent, &n) - Integer::ONE).gcd(&n) == 1
+        });
+
+        if let Some(witness) = witness {
+            let certificate = PrimalityCertificate::Pocklington {
+                p: n.clone(),
+                factors: vec![PocklingtonFactor {
+                    prime: q.clone(),
+                    exponent: 1,
+                    witness,
+                    certificate: q_certificate.clone(),
+                }],
+            };
+            return (n, certificate);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use rug::{Complete, Integer};
+use crate::algebraic_structure::finite_field::MultiplicativeGroup;
+use crate::integers::integer_computations::pow_rug;
+use crate::integers::prime::{find_prime_with_bit_length, is_likely_prime_with_trial_division, rabin_miller_is_prime, rabin_miller_is_prime_with_bases};
+use crate::integers::sieve::PrimeTable;
+use crate::random::{randint_below, randint_bits_odd};
+
+
+const SIEVE_BOUND: u64 = 10000;
+const TRIAL_DIVISION_BOUND: usize = 1000;
+
+
+// Searches the candidates q = a, a+2, ..., a+2(d-1) for q and p = 2q + 1 both prime. A small prime
+// r < a rules out q when r | q or r | 2q + 1, i.e. q = 0 or q = (r-1)/2 (mod r), so both
+// conditions are sieved away at once before any Rabin-Miller test is done.
+fn find_safe_prime_in_interval_with_sieving(a: &Integer, d: usize, t: usize, bits: u32) -> Option<Integer> {
+    let mut candidates: Vec<bool> = vec![true; d];
+
+    let bound = a.to_u64().map_or(SIEVE_BOUND, |a| SIEVE_BOUND.min(a - 1));
+    for r in PrimeTable::up_to(bound).iter().skip(1) {
+        let a_mod_r = a.mod_u(r as u32) as u64;
+        let inverse_of_two = r.div_ceil(2);
+        let q_divisible = (r - a_mod_r) % r * inverse_of_two % r;
+        let p_divisible = ((r - 1)/2 + r - a_mod_r) % r * inverse_of_two % r;
+
+        for start in [q_divisible, p_divisible] {
+            for index in (start as usize..d).step_by(r as usize) {
+                candidates[index] = false;
+            }
+        }
+    }
+
+    for (i, _) in candidates.iter().enumerate().filter(|(_, is_candidate)| **is_candidate) {
+        let q: Integer = (a + 2*i as u64).complete();
+        let p: Integer = (&q*2u32).complete() + 1;
+        if p.significant_bits() > bits {
+            return None;
+        }
+        // A single base first, so most composites only cost one exponentiation.
+        if rabin_miller_is_prime_with_bases(&q, &[2]) && rabin_miller_is_prime_with_bases(&p, &[2])
+            && rabin_miller_is_prime(&q, t) && rabin_miller_is_prime(&p, t) {
+            return Some(p);
+        }
+    }
+
+    None
+}
+
+
+// A prime p = 2q + 1 with q prime and p of exactly `bits` bits.
+pub fn find_safe_prime_with_bit_length(bits: usize, t: usize) -> Integer {
+    if bits < 3 {
+        panic!("There are no safe primes with less than 3 bits");
+    }
+    // Both q and 2q + 1 have to be prime, so roughly (bits*ln 2)^2 candidates are needed, of which
+    // the sieve keeps a small fraction.
+    let d = 4*bits*bits;
+
+    loop {
+        let a = randint_bits_odd(bits - 1);
+        if let Some(p) = find_safe_prime_in_interval_with_sieving(&a, d, t, bits as u32) {
+            return p
+        }
+    }
+}
+
+
+// A prime q of exactly `bits` bits such that 2q + 1 is also prime.
+pub fn find_sophie_germain_prime_with_bit_length(bits: usize, t: usize) -> Integer {
+    let p = find_safe_prime_with_bit_length(bits + 1, t);
+    (p - 1u32) >> 1u32
+}
+
+
+// Z_p^* for a safe prime p = 2q + 1, together with the subgroup of quadratic residues which has
+// prime order q. It is generated by 4 = 2^2, since 4 != 1 and its order divides q.
+pub fn find_safe_prime_group(bits: usize, t: usize) -> MultiplicativeGroup {
+    let p = find_safe_prime_with_bit_length(bits, t);
+    let q: Integer = (&p - 1u32).complete() >> 1u32;
+    MultiplicativeGroup::with_subgroup(p, q, Integer::from(4))
+}
+
+
+// Schnorr group parameters: primes p = kq + 1 and q of exactly `p_bits` and `q_bits` bits, and a
+// generator g = h^k (mod p) of the subgroup of order q in Z_p^*.
+pub fn find_schnorr_group(p_bits: usize, q_bits: usize, t: usize) -> MultiplicativeGroup {
+    if q_bits < 2 || p_bits <= q_bits + 1 {
+        panic!("Need p_bits > q_bits + 1 and q_bits >= 2, got p_bits = {}, q_bits = {}", p_bits, q_bits);
+    }
+    let q = find_prime_with_bit_length(q_bits, t);
+
+    // 2^(p_bits-1) <= kq + 1 < 2^p_bits
+    let k_min: Integer = ((Integer::from(1) << (p_bits as u32 - 1)) - 1u32 + &q - 1u32) / &q;
+    let k_max: Integer = ((Integer::from(1) << p_bits as u32) - 2u32) / &q;
+    let width: Integer = (&k_max - &k_min).complete() + 1;
+
+    let (p, k) = loop {
+        let mut k: Integer = &k_min + randint_below(&width);
+        // kq + 1 is even for k odd.
+        if k.is_odd() {
+            k += 1;
+            if k > k_max {
+                continue;
+            }
+        }
+        let p: Integer = (&k*&q).complete() + 1;
+        if is_likely_prime_with_trial_division(&p, t, TRIAL_DIVISION_BOUND) {
+            break (p, k);
+        }
+    };
+
+    // h^k has order dividing q, so it generates the subgroup unless it is 1.
+    let p_minus_three: Integer = (&p - 3u32).complete();
+    loop {
+        let h: Integer = randint_below(&p_minus_three) + 2;
+        let g = pow_rug(&h, &k, &p);
+        if g != 1 {
+            return MultiplicativeGroup::with_subgroup(p, q, g);
+        }
+    }
+}
@@ -63,6 +63,65 @@ pub fn pow_rug(a: &Integer, b: &Integer, n: &Integer) -> Integer {
 }
 
 
+// Uses native 128-bit multiplication and therefore avoids allocating with rug.
+pub fn mul_mod_u64(a: u64, b: u64, n: u64) -> u64 {
+    ((a as u128 * b as u128) % n as u128) as u64
+}
+
+
+pub fn pow_mod_u64(a: u64, b: u64, n: u64) -> u64 {
+    let mut product: u64 = 1 % n;
+    let mut base = a % n;
+    let mut exponent = b;
+
+    while exponent != 0 {
+        if exponent & 1 == 1 {
+            product = mul_mod_u64(product, base, n);
+        }
+        base = mul_mod_u64(base, base, n);
+        exponent >>= 1;
+    }
+    product
+}
+
+
+// A square root of a modulo the odd prime p by Tonelli-Shanks, or None if a is a non-residue.
+// Write p - 1 = 2^s q with q odd and take a non-residue z. Then x = a^((q+1)/2) has x^2 = ab with
+// b = a^q of order 2^m, and b is made 1 by multiplying with powers of z^q of decreasing order.
+pub fn sqrt_mod_u64(a: u64, p: u64) -> Option<u64> {
+    let a = a % p;
+    if a == 0 || p == 2 {
+        return Some(a);
+    }
+    if pow_mod_u64(a, (p - 1)/2, p) != 1 {
+        return None;
+    }
+
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let z = (2..p).find(|z| pow_mod_u64(*z, (p - 1)/2, p) == p - 1).expect("Half of the residues are non-residues");
+
+    let mut x = pow_mod_u64(a, q.div_ceil(2), p);
+    let mut b = pow_mod_u64(a, q, p);
+    let mut c = pow_mod_u64(z, q, p);
+    let mut m = s;
+    while b != 1 {
+        let mut i = 0;
+        let mut power = b;
+        while power != 1 {
+            power = mul_mod_u64(power, power, p);
+            i += 1;
+        }
+        let t = pow_mod_u64(c, 1 << (m - i - 1), p);
+        x = mul_mod_u64(x, t, p);
+        c = mul_mod_u64(t, t, p);
+        b = mul_mod_u64(b, c, p);
+        m = i;
+    }
+    Some(x)
+}
+
+
 pub fn naive_pow(a: &Integer, b: &Integer, n: &Integer) -> Integer {
     let mut product: Integer = Integer::ONE.clone();
     let s = b.to_u64().expect("The number is WAY too high to naively calculate.");
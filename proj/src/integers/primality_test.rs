@@ -0,0 +1,234 @@
+use std::time::Instant;
+use rug::Integer;
+use crate::integers::prime::{baillie_psw_is_prime, fermat_is_prime, rabin_miller_is_prime, rabin_miller_is_prime_counting_rounds, rabin_miller_is_prime_deterministic, solovay_strassen_is_prime};
+use crate::integers::prime_statistics::PrimeSearchStatistics;
+use crate::integers::sieve::PrimeTable;
+
+
+// A (probabilistic) primality test. The prime generators are generic over this trait, so that
+// they can be compared with different tests without copying the search itself.
+pub trait PrimalityTest {
+    fn is_prime(&self, n: &Integer) -> bool;
+
+    // As is_prime, but also adds the work done to the statistics. By default only the time is
+    // recorded.
+    fn is_prime_with_statistics(&self, n: &Integer, statistics: &mut PrimeSearchStatistics) -> bool {
+        let now = Instant::now();
+        let is_prime = self.is_prime(n);
+        statistics.primality_test_time += now.elapsed();
+        is_prime
+    }
+
+    // n is only tested by `other` if it passes `self`, so the cheaper test should come first.
+    fn and<T: PrimalityTest>(self, other: T) -> And<Self, T>
+    where
+        Self: Sized,
+    {
+        And { first: self, second: other }
+    }
+}
+
+
+impl<T: PrimalityTest + ?Sized> PrimalityTest for &T {
+    fn is_prime(&self, n: &Integer) -> bool {
+        (**self).is_prime(n)
+    }
+
+
+    fn is_prime_with_statistics(&self, n: &Integer, statistics: &mut PrimeSearchStatistics) -> bool {
+        (**self).is_prime_with_statistics(n, statistics)
+    }
+}
+
+
+impl<T: PrimalityTest + ?Sized> PrimalityTest for Box<T> {
+    fn is_prime(&self, n: &Integer) -> bool {
+        (**self).is_prime(n)
+    }
+
+
+    fn is_prime_with_statistics(&self, n: &Integer, statistics: &mut PrimeSearchStatistics) -> bool {
+        (**self).is_prime_with_statistics(n, statistics)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Fermat {
+    reps: usize,
+}
+
+
+impl Fermat {
+    pub fn new(reps: usize) -> Fermat {
+        Fermat { reps }
+    }
+}
+
+
+impl PrimalityTest for Fermat {
+    fn is_prime(&self, n: &Integer) -> bool {
+        fermat_is_prime(n, self.reps)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct RabinMiller {
+    reps: usize,
+}
+
+
+impl RabinMiller {
+    pub fn new(reps: usize) -> RabinMiller {
+        RabinMiller { reps }
+    }
+}
+
+
+impl PrimalityTest for RabinMiller {
+    fn is_prime(&self, n: &Integer) -> bool {
+        rabin_miller_is_prime(n, self.reps)
+    }
+
+
+    fn is_prime_with_statistics(&self, n: &Integer, statistics: &mut PrimeSearchStatistics) -> bool {
+        let now = Instant::now();
+        let (is_prime, rounds) = rabin_miller_is_prime_counting_rounds(n, self.reps);
+        statistics.rabin_miller_rounds += rounds;
+        statistics.primality_test_time += now.elapsed();
+        is_prime
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct SolovayStrassen {
+    reps: usize,
+}
+
+
+impl SolovayStrassen {
+    pub fn new(reps: usize) -> SolovayStrassen {
+        SolovayStrassen { reps }
+    }
+}
+
+
+impl PrimalityTest for SolovayStrassen {
+    fn is_prime(&self, n: &Integer) -> bool {
+        solovay_strassen_is_prime(n, self.reps)
+    }
+}
+
+
+// Exact below 3.3*10^24, above that `reps` random bases are used.
+#[derive(Debug, Clone, Copy)]
+pub struct DeterministicRabinMiller {
+    reps: usize,
+}
+
+
+impl DeterministicRabinMiller {
+    pub fn new(reps: usize) -> DeterministicRabinMiller {
+        DeterministicRabinMiller { reps }
+    }
+}
+
+
+impl PrimalityTest for DeterministicRabinMiller {
+    fn is_prime(&self, n: &Integer) -> bool {
+        rabin_miller_is_prime_deterministic(n).unwrap_or_else(|| rabin_miller_is_prime(n, self.reps))
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct BailliePSW;
+
+
+impl PrimalityTest for BailliePSW {
+    fn is_prime(&self, n: &Integer) -> bool {
+        baillie_psw_is_prime(n)
+    }
+}
+
+
+// Rejects n with a prime factor p <= bound (other than n itself) before running the inner test.
+// A bound of 0 runs the inner test directly.
+#[derive(Debug, Clone)]
+pub struct TrialDivision<T> {
+    primes: PrimeTable,
+    test: T,
+}
+
+
+impl<T: PrimalityTest> TrialDivision<T> {
+    pub fn new(bound: u64, test: T) -> TrialDivision<T> {
+        TrialDivision { primes: PrimeTable::up_to(bound), test }
+    }
+
+
+    pub fn bound(&self) -> u64 {
+        self.primes.largest().unwrap_or(0)
+    }
+
+
+    // Some(answer) when trial division alone decides n.
+    fn trial_divide(&self, n: &Integer) -> Option<bool> {
+        if *n < 2 {
+            return Some(false);
+        }
+        for p in self.primes.iter() {
+            if *n == p {
+                return Some(true);
+            }
+            if n.is_divisible_u(p as u32) {
+                return Some(false);
+            }
+        }
+        None
+    }
+}
+
+
+impl<T: PrimalityTest> PrimalityTest for TrialDivision<T> {
+    fn is_prime(&self, n: &Integer) -> bool {
+        self.trial_divide(n).unwrap_or_else(|| self.test.is_prime(n))
+    }
+
+
+    fn is_prime_with_statistics(&self, n: &Integer, statistics: &mut PrimeSearchStatistics) -> bool {
+        let now = Instant::now();
+        let answer = self.trial_divide(n);
+        statistics.trial_division_time += now.elapsed();
+        match answer {
+            Some(false) => {
+                statistics.rejected_by_trial_division += 1;
+                false
+            },
+            Some(true) => true,
+            None => self.test.is_prime_with_statistics(n, statistics),
+        }
+    }
+}
+
+
+// Composite if either test says so, built with PrimalityTest::and.
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B> {
+    first: A,
+    second: B,
+}
+
+
+impl<A: PrimalityTest, B: PrimalityTest> PrimalityTest for And<A, B> {
+    fn is_prime(&self, n: &Integer) -> bool {
+        self.first.is_prime(n) && self.second.is_prime(n)
+    }
+
+
+    fn is_prime_with_statistics(&self, n: &Integer, statistics: &mut PrimeSearchStatistics) -> bool {
+        self.first.is_prime_with_statistics(n, statistics) && self.second.is_prime_with_statistics(n, statistics)
+    }
+}
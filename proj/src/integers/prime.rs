@@ -1,27 +1,23 @@
+use std::{fs, str::FromStr, time::Instant};
 use rand::{thread_rng, Rng};
+use rayon::prelude::*;
 use rug::rand::RandState;
 use rug::{Complete, Integer};
-use std::fs;
-use std::io::{BufReader, BufRead};
-use std::str::FromStr;
-use crate::random::{randint_bits_odd, randint_bits};
-use crate::integers::integer_computations::pow_rug;
+use crate::random::{randint_below, randint_bits_odd, randint_bits};
+use crate::integers::sieve::PrimeTable;
+use crate::integers::primality_test::{PrimalityTest, RabinMiller, TrialDivision};
+use crate::integers::prime_statistics::{approx_width_in_random_interval_search, PrimeSearchStatistics};
+use crate::integers::integer_computations::{mul_mod_u64, pow_mod_u64, pow_rug};
 
 
 pub fn is_likely_prime_with_trial_division(candidate: &Integer, n: usize, bound: usize) -> bool {
     if bound == 0 {
         return rabin_miller_is_prime(candidate, n);
     }
-    let small_primes = fs::File::open("small-primes").expect("small-primes file should have been generated");
-    let reader = BufReader::new(small_primes);
-    for prime in reader.lines() {
-        let p: Integer = Integer::from_str(&prime.unwrap()).expect("All entries of small-primes should be integers.");
-        if p > bound {
-            break;
-        }
-        if candidate > &p && candidate%p == 0 {
+    for p in PrimeTable::up_to(bound as u64).iter() {
+        if *candidate > p && (candidate % p).complete() == 0 {
             return false
-        }         
+        }
     }
 
     rabin_miller_is_prime(candidate, n)
@@ -40,51 +36,381 @@ pub fn fermat_is_prime(n: &Integer, reps: usize) -> bool {
 }
 
 
+// For n an odd prime and a in [1, n-1] Euler's criterion gives
+//      a^((n-1)/2) = (a/n) (mod n)
+// and at most half of the a coprime to n satisfy this when n is composite, Carmichael numbers
+// included.
+pub fn solovay_strassen_is_prime(n: &Integer, reps: usize) -> bool {
+    if *n == 2 || *n == 3 {
+        return true;
+    } else if *n < 2 || n.is_even() {
+        return false;
+    }
+
+    let mut rng = RandState::new();
+    let n_minus_one = (n - Integer::ONE).complete();
+    let exponent = (&n_minus_one >> 1u32).complete();
+
+    for _ in 0..reps {
+        let a = Integer::from(2) + (n-Integer::from(3)).random_below(&mut rng);
+        let expected = match a.jacobi(n) {
+            0 => return false,
+            1 => Integer::ONE.clone(),
+            _ => n_minus_one.clone(),
+        };
+        if pow_rug(&a, &exponent, n) != expected {
+            return false;
+        }
+    }
+    true
+}
+
+
+// Witnesses making Rabin-Miller deterministic for every n < 2^64, found by Jim Sinclair.
+const DETERMINISTIC_BASES_U64: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+
+// The first 13 primes are witnesses for every n < 3.3*10^24 (Sorenson and Webster).
+const DETERMINISTIC_BASES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+const DETERMINISTIC_BOUND: u128 = 3_317_044_064_679_887_385_961_981;
+const FULLY_SIEVED_RANGE: u64 = 1 << 20;
+
+
 // For n an odd prime with n-1 = 2^s * r with r odd and a in [1, n-1] we have: 
 //      a^r = 1 (mod n)    or    a^(2^j * r) = -1 (mod n), for j in [0, s-1]
-pub fn rabin_miller_is_prime(n: &Integer, reps: usize) -> bool {
-    if *n == 2 {
-        return true;
-    } else if *n == 3 {
+fn is_strong_probable_prime(n: &Integer, a: &Integer, r: &Integer, s: usize) -> bool {
+    let n_minus_one = (n - Integer::ONE).complete();
+    let mut y = pow_rug(a, r, n);
+
+    if &y == Integer::ONE || y == n_minus_one {
         return true;
     }
 
-    let mut rng = RandState::new();
+    for _ in 1..s {
+        y = pow_rug(&y, &Integer::from(2), n);
+        if y == n_minus_one {
+            return true;
+        }
+        if y == 1 {
+            return false;
+        }
+    }
+
+    false
+}
+
+
+// Writes n-1 = 2^s * r with r odd.
+fn split_off_powers_of_two(n: &Integer) -> (Integer, usize) {
     let mut r: Integer = n.clone() - Integer::ONE;
     let mut s = 0;
     while !r.get_bit(0) {
-        r = r >> 1;
+        r >>= 1;
         s += 1;
     }
+    (r, s)
+}
 
-    for _ in 0..reps {
+
+pub fn rabin_miller_is_prime(n: &Integer, reps: usize) -> bool {
+    rabin_miller_is_prime_counting_rounds(n, reps).0
+}
+
+
+// Also returns the number of rounds done, which is less than reps when a witness is found early.
+pub fn rabin_miller_is_prime_counting_rounds(n: &Integer, reps: usize) -> (bool, usize) {
+    if *n == 2 {
+        return (true, 0);
+    } else if *n == 3 {
+        return (true, 0);
+    } else if *n < 2 || n.is_even() {
+        return (false, 0);
+    }
+
+    let mut rng = RandState::new();
+    let (r, s) = split_off_powers_of_two(n);
+
+    for round in 1..=reps {
         let a = Integer::from(2) + (n-Integer::from(4)).random_below(&mut rng);
-        let mut y = pow_rug(&a, &r, n);
+        if !is_strong_probable_prime(n, &a, &r, s) {
+            return (false, round);
+        }
+    }
 
-        if &y != Integer::ONE && y != (n-Integer::ONE).complete() {
-            let mut j = 1;
+    (true, reps)
+}
 
-            while j <= s-1 && y != (n-Integer::ONE).complete() {
-                y = pow_rug(&y, &Integer::from(2), n);
-                if y == 1 {
-                    return false;
-                }
-                j += 1;
-            }
 
-            if y != (n-Integer::ONE).complete() {
-                return false;
+pub fn rabin_miller_is_prime_with_bases(n: &Integer, bases: &[u64]) -> bool {
+    if *n < 2 {
+        return false;
+    } else if *n < 4 {
+        return true;
+    } else if n.is_even() {
+        return false;
+    }
+
+    let (r, s) = split_off_powers_of_two(n);
+    for base in bases {
+        let a = Integer::from(*base) % n;
+        if a.is_zero() {
+            continue;
+        }
+        if !is_strong_probable_prime(n, &a, &r, s) {
+            return false;
+        }
+    }
+
+    true
+}
+
+
+// Gives a definite answer for n < 3.3*10^24 and None above, where no small witness set is known.
+pub fn rabin_miller_is_prime_deterministic(n: &Integer) -> Option<bool> {
+    if *n < 2 {
+        return Some(false);
+    }
+    if let Some(m) = n.to_u64() {
+        return Some(rabin_miller_is_prime_u64(m));
+    }
+    if *n < DETERMINISTIC_BOUND {
+        Some(rabin_miller_is_prime_with_bases(n, &DETERMINISTIC_BASES))
+    } else {
+        None
+    }
+}
+
+
+pub fn rabin_miller_is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    rabin_miller_is_prime_with_bases_u64(n, &DETERMINISTIC_BASES_U64)
+}
+
+
+// As rabin_miller_is_prime_with_bases without leaving u64, so composites with small factors are
+// only rejected if some base is a witness.
+pub fn rabin_miller_is_prime_with_bases_u64(n: u64, bases: &[u64]) -> bool {
+    if n < 2 {
+        return false;
+    } else if n < 4 {
+        return true;
+    } else if n.is_multiple_of(2) {
+        return false;
+    }
+
+    let s = (n-1).trailing_zeros();
+    let r = (n-1) >> s;
+
+    'bases: for base in bases {
+        let a = base % n;
+        if a == 0 {
+            continue;
+        }
+        let mut y = pow_mod_u64(a, r, n);
+        if y == 1 || y == n-1 {
+            continue;
+        }
+        for _ in 1..s {
+            y = mul_mod_u64(y, y, n);
+            if y == n-1 {
+                continue 'bases;
             }
         }
+        return false;
     }
 
     true
 }
 
 
+// Computes (U_k, V_k, Q^k) modulo n for the Lucas sequences with parameters P and Q, where
+//      U_0 = 0, U_1 = 1, V_0 = 2, V_1 = P    and    X_{j+1} = P*X_j - Q*X_{j-1}.
+// Uses the doubling formulas U_2m = U_m*V_m, V_2m = V_m^2 - 2*Q^m and for the step m -> m+1
+//      U_{m+1} = (P*U_m + V_m)/2,    V_{m+1} = (D*U_m + P*V_m)/2,    with D = P^2 - 4Q,
+// so n must be odd for the halving to make sense.
+fn lucas_sequence_with_power(p: &Integer, q: &Integer, k: &Integer, n: &Integer) -> (Integer, Integer, Integer) {
+    if n.is_even() {
+        panic!("Lucas sequences are only computed modulo odd integers");
+    }
+    if k.is_zero() {
+        return (Integer::ZERO.clone(), Integer::from(2).modulo(n), Integer::ONE.clone().modulo(n));
+    }
+
+    let p = p.clone().modulo(n);
+    let q = q.clone().modulo(n);
+    let d = (p.clone().square() - Integer::from(4) * &q).modulo(n);
+    let half = |x: Integer| -> Integer {
+        if x.is_odd() {
+            (x + n) >> 1
+        } else {
+            x >> 1
+        }
+    };
+
+    let mut u = Integer::ONE.clone().modulo(n);
+    let mut v = p.clone();
+    let mut q_k = q.clone();
+
+    for i in (0..k.significant_bits()-1).rev() {
+        u = (&u * &v).complete().modulo(n);
+        v = (v.square() - Integer::from(2) * &q_k).modulo(n);
+        q_k = q_k.square().modulo(n);
+
+        if k.get_bit(i) {
+            let u_next = half((&p * &u).complete() + &v);
+            v = half((&d * &u).complete() + &p * &v);
+            u = u_next.modulo(n);
+            v = v.modulo(n);
+            q_k = (q_k * &q).modulo(n);
+        }
+    }
+
+    (u, v, q_k)
+}
+
+
+pub fn lucas_sequence(p: &Integer, q: &Integer, k: &Integer, n: &Integer) -> (Integer, Integer) {
+    let (u, v, _) = lucas_sequence_with_power(p, q, k, n);
+    (u, v)
+}
+
+
+// Selfridge's method A: the first D in 5, -7, 9, -11, ... with (D/n) = -1, and P = 1,
+// Q = (1 - D)/4. Returns None if n is found to be composite on the way.
+fn selfridge_parameters(n: &Integer) -> Option<(Integer, Integer, Integer)> {
+    if n.is_perfect_square() {
+        return None;
+    }
+
+    let mut d = Integer::from(5);
+    loop {
+        let jacobi = d.jacobi(n);
+        if jacobi == -1 {
+            break;
+        }
+        if jacobi == 0 && d.clone().abs() != *n {
+            return None;
+        }
+        d = if d > 0 { -(d + 2u32) } else { -(d - 2u32) };
+    }
+
+    let q = (Integer::ONE - &d).complete() / 4;
+    Some((Integer::ONE.clone(), q, d))
+}
+
+
+// For n an odd prime not dividing QD we have U_(n+1) = 0 (mod n). Every strong Lucas probable
+// prime passes this, but not the other way around, e.g. 323 = 17*19.
+pub fn lucas_is_prime(n: &Integer) -> bool {
+    if *n < 2 {
+        return false;
+    } else if *n == 2 {
+        return true;
+    } else if n.is_even() {
+        return false;
+    }
+
+    let (p, q, _) = match selfridge_parameters(n) {
+        Some(parameters) => parameters,
+        None => return false,
+    };
+
+    let (u, _) = lucas_sequence(&p, &q, &(n + Integer::ONE).complete(), n);
+    u.is_zero()
+}
+
+
+// For n an odd prime not dividing QD with n+1 = 2^s * d and d odd we have:
+//      U_d = 0 (mod n)    or    V_(2^r * d) = 0 (mod n), for r in [0, s-1]
+pub fn strong_lucas_is_prime(n: &Integer) -> bool {
+    if *n < 2 {
+        return false;
+    } else if *n == 2 {
+        return true;
+    } else if n.is_even() {
+        return false;
+    }
+
+    let (p, q, _) = match selfridge_parameters(n) {
+        Some(parameters) => parameters,
+        None => return false,
+    };
+
+    let mut d = (n + Integer::ONE).complete();
+    let mut s = 0;
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+
+    let (u, mut v, mut q_k) = lucas_sequence_with_power(&p, &q, &d, n);
+    if u.is_zero() {
+        return true;
+    }
+    for _ in 0..s {
+        if v.is_zero() {
+            return true;
+        }
+        v = (v.square() - Integer::from(2) * &q_k).modulo(n);
+        q_k = q_k.square().modulo(n);
+    }
+
+    false
+}
+
+
+// A strong probable prime test to base 2 followed by a strong Lucas test. There are no known
+// composites passing both, and none below 2^64.
+pub fn baillie_psw_is_prime(n: &Integer) -> bool {
+    if *n < 2 {
+        return false;
+    }
+    for p in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47] {
+        if *n == p {
+            return true;
+        }
+        if n.is_divisible_u(p) {
+            return false;
+        }
+    }
+    if let Some(m) = n.to_u64() {
+        return rabin_miller_is_prime_u64(m);
+    }
+
+    rabin_miller_is_prime_with_bases(n, &[2]) && strong_lucas_is_prime(n)
+}
+
+
+// Runs the test and, when statistics are collected, counts n as a candidate.
+fn test_candidate<T: PrimalityTest + ?Sized>(n: &Integer, test: &T, statistics: &mut Option<&mut PrimeSearchStatistics>) -> bool {
+    match statistics {
+        Some(statistics) => {
+            statistics.candidates += 1;
+            test.is_prime_with_statistics(n, statistics)
+        },
+        None => test.is_prime(n),
+    }
+}
+
+
 pub fn find_prime_with_bit_length(bits: usize, t: usize) -> Integer {
+    find_prime_with_bit_length_with_test(bits, &RabinMiller::new(t), None)
+}
+
+
+pub fn find_prime_with_bit_length_with_test<T: PrimalityTest + ?Sized>(bits: usize, test: &T, mut statistics: Option<&mut PrimeSearchStatistics>) -> Integer {
+    if let Some(statistics) = statistics.as_deref_mut() {
+        statistics.searches += 1;
+    }
     let mut p: Integer = randint_bits_odd(bits);
-    while !rabin_miller_is_prime(&p, t) {
+    while !test_candidate(&p, test, &mut statistics) {
         p = randint_bits_odd(bits);
     }
     p
@@ -92,107 +418,337 @@ pub fn find_prime_with_bit_length(bits: usize, t: usize) -> Integer {
 
 
 pub fn find_prime_with_bit_length_using_trial_division(bits: usize, t: usize, bound: usize) -> Integer {
-    let mut p: Integer = randint_bits_odd(bits);
-    while !is_likely_prime_with_trial_division(&p, t, bound) {
-        p = randint_bits_odd(bits);
-    }
-    p
+    find_prime_with_bit_length_with_test(bits, &TrialDivision::new(bound as u64, RabinMiller::new(t)), None)
 }
 
 
 pub fn find_prime_with_bit_length_using_interval(bits: usize, d: usize, t: usize, bound: usize) -> Option<Integer> {
-    let mut n = randint_bits(bits);
-    if is_likely_prime_with_trial_division(&n, t, bound) {
-        return Some(n)
+    find_prime_with_bit_length_using_interval_with_test(bits, d, &TrialDivision::new(bound as u64, RabinMiller::new(t)), None)
+}
+
+
+// Tests the odd numbers in a random interval [a, a + d) of `bits`-bit integers. The interval is cut
+// at 2^bits, so a prime which is found always has exactly `bits` bits.
+pub fn find_prime_with_bit_length_using_interval_with_test<T: PrimalityTest + ?Sized>(bits: usize, d: usize, test: &T, mut statistics: Option<&mut PrimeSearchStatistics>) -> Option<Integer> {
+    if let Some(statistics) = statistics.as_deref_mut() {
+        statistics.searches += 1;
+        statistics.intervals += 1;
+    }
+    let (a, d) = random_interval_with_bit_length(bits, d);
+    let end: Integer = (&a + d as u64).complete();
+
+    if a == 2 && test_candidate(&a, test, &mut statistics) {
+        return Some(a)
     }
-    for _ in 0..d {
-        n += 1;
-        if is_likely_prime_with_trial_division(&n, t, bound) {
+    let mut n = a | 1u32;
+    while n < end {
+        if test_candidate(&n, test, &mut statistics) {
             return Some(n)
         }
+        n += 2;
     }
 
     None
 }
 
 
-pub fn find_prime_in_interval_with_sieving(a: &Integer, d: usize, t: usize, bound: usize) -> Option<Integer> {
-    let small_primes = fs::File::open("small-primes").expect("small-primes file should have been generated");
-    let reader = BufReader::new(small_primes);
+// A uniformly random start a of `bits` bits and the width of [a, a + d) after cutting it at
+// 2^bits.
+fn random_interval_with_bit_length(bits: usize, d: usize) -> (Integer, usize) {
+    let a = randint_bits(bits);
+    let upper: Integer = Integer::from(1) << bits as u32;
+    let room = (upper - &a).to_usize().unwrap_or(usize::MAX);
+    (a, d.min(room))
+}
+
 
+// Indices i in [0, d) such that a + i*m has no prime factor r <= bound other than r itself and,
+// for the primes r <= bound dividing coprime_to, a + i*m != 1 (mod r).
+fn sieve_arithmetic_progression(a: &Integer, m: &Integer, d: usize, bound: usize, coprime_to: Option<&Integer>) -> Vec<usize> {
     let mut vec: Vec<bool> = vec![true; d];
-    let mut capacity = d;
 
-    for prime in reader.lines() {
-        let p = prime.expect("Line should exist").parse::<usize>().expect("Lines should be primes");
-        if p > bound {
-            break;
+    for r in PrimeTable::up_to(bound as u64).iter() {
+        let a_mod_r = a.mod_u(r as u32) as u64;
+        let m_mod_r = m.mod_u(r as u32) as u64;
+        let mut forbidden = vec![0];
+        if coprime_to.is_some_and(|e| e.is_divisible_u(r as u32)) {
+            forbidden.push(1 % r);
         }
 
-        let off_set: usize = (p - (a%p).complete()).to_usize().expect("Should be small enough");
-        let mut count = 0;
-        loop {
-            let index: usize = count*p + &off_set;
-            if index >= d {
-                break;
-            }
-
-            if vec[index] {
+        for residue in forbidden {
+            let (off_set, step) = if m_mod_r == 0 {
+                if a_mod_r != residue {
+                    continue;
+                }
+                (0, 1)
+            } else {
+                // a + i*m = residue (mod r)  <=>  i = (residue - a) * m^-1 (mod r)
+                let m_inverse = pow_mod_u64(m_mod_r, r - 2, r);
+                (mul_mod_u64((residue + r - a_mod_r) % r, m_inverse, r) as usize, r as usize)
+            };
+            // The first index is the only one which can be r itself.
+            let start = if residue == 0 && *a <= r && (a + (m*off_set as u64).complete()) == r {
+                off_set + step
+            } else {
+                off_set
+            };
+            for index in (start..d).step_by(step) {
                 vec[index] = false;
-                capacity -= 1;
             }
-            count += 1;
         }
     }
 
-    let mut sieving_vec: Vec<usize> = Vec::with_capacity(capacity);   
+    vec.iter()
+        .enumerate()
+        .filter(|(_, j)| **j)
+        .map(|(i, _)| i)
+        .collect()
+}
+
 
-    for (i, j) in vec.iter().enumerate() {
-        if *j {
-            sieving_vec.push(i);
+pub fn find_prime_in_interval_with_sieving(a: &Integer, d: usize, t: usize, bound: usize) -> Option<Integer> {
+    find_prime_in_interval_with_sieving_with_test(a, d, bound, &RabinMiller::new(t), None)
+}
+
+
+pub fn find_prime_in_interval_with_sieving_with_test<T: PrimalityTest + ?Sized>(a: &Integer, d: usize, bound: usize, test: &T, mut statistics: Option<&mut PrimeSearchStatistics>) -> Option<Integer> {
+    let now = Instant::now();
+    let mut sieving_vec = sieve_arithmetic_progression(a, Integer::ONE, d, bound, None);
+    if let Some(statistics) = statistics.as_deref_mut() {
+        statistics.intervals += 1;
+        statistics.rejected_by_sieving += d - sieving_vec.len();
+        statistics.sieving_time += now.elapsed();
+    }
+
+    // Every survivor is equally likely to be tested next, and a tested one is never tested again.
+    let mut rng = thread_rng();
+    while !sieving_vec.is_empty() {
+        let index = rng.gen_range(0..sieving_vec.len());
+        let p: Integer = (a + sieving_vec.swap_remove(index) as u64).complete();
+        if test_candidate(&p, test, &mut statistics) {
+            return Some(p)
         }
     }
 
-    if sieving_vec.len() == 0 {
+    None
+}
+
+
+pub fn find_prime_with_bit_length_using_sieving(bits: usize, t: usize, bound: usize) -> Integer {
+    find_prime_with_bit_length_using_sieving_with_test(bits, bound, &RabinMiller::new(t), None)
+}
+
+
+pub fn find_prime_with_bit_length_using_sieving_with_test<T: PrimalityTest + ?Sized>(bits: usize, bound: usize, test: &T, mut statistics: Option<&mut PrimeSearchStatistics>) -> Integer {
+    if bound == 0 {
+        return find_prime_with_bit_length_with_test(bits, test, statistics);
+    }
+    let probability = 0.95;
+    let d = approx_width_in_random_interval_search(bits, probability).max(1);
+    if let Some(statistics) = statistics.as_deref_mut() {
+        statistics.searches += 1;
+    }
+
+    loop {
+        let (a, d) = random_interval_with_bit_length(bits, d);
+        if let Some(p) = find_prime_in_interval_with_sieving_with_test(&a, d, bound, test, statistics.as_deref_mut()) {
+            return p
+        }
+    }
+}
+
+
+pub fn find_prime_in_range(a: &Integer, b: &Integer, t: usize, bound: usize) -> Option<Integer> {
+    find_prime_in_range_with_test(a, b, bound, &RabinMiller::new(t), None)
+}
+
+
+// A uniformly random prime p with a <= p <= b, or None if there is none. Ranges of up to
+// FULLY_SIEVED_RANGE integers are sieved as a whole and the survivors tested in random order.
+// Larger ranges always contain primes in practice, and there random integers are tried after
+// trial division up to the bound.
+pub fn find_prime_in_range_with_test<T: PrimalityTest + ?Sized>(a: &Integer, b: &Integer, bound: usize, test: &T, mut statistics: Option<&mut PrimeSearchStatistics>) -> Option<Integer> {
+    let a = a.clone().max(Integer::from(2));
+    if *b < a {
         return None
     }
+    if let Some(statistics) = statistics.as_deref_mut() {
+        statistics.searches += 1;
+    }
 
-    let mut rng = thread_rng();
-    let mut index = rng.gen_range(0..capacity);
-    let mut p: Integer = (a + sieving_vec[index]).into();
+    let width: Integer = (b - &a).complete() + 1;
+    if let Some(d) = width.to_u64().filter(|d| *d <= FULLY_SIEVED_RANGE) {
+        return find_prime_in_interval_with_sieving_with_test(&a, d as usize, bound, test, statistics);
+    }
 
-    while !rabin_miller_is_prime(&p, t) {
-        sieving_vec.remove(index);
-        if sieving_vec.len() == 0 {
-            return None
+    let test = TrialDivision::new(bound as u64, test);
+    loop {
+        let n: Integer = &a + randint_below(&width);
+        if test_candidate(&n, &test, &mut statistics) {
+            return Some(n)
         }
-        capacity -= 1;
-        p = (a + sieving_vec[rng.gen_range(0..capacity)]).into();
-        index = rng.gen_range(0..capacity);
     }
-    Some(p)
 }
 
 
-fn approx_width_in_random_interval_search(bits: usize, probability: f64) -> usize {
-    ( - probability/( (1f64 - 1f64/((bits as f64)*2f64.ln())) ).ln() ) as usize
+pub fn find_prime_with_bit_length_using_sieving_parallel(bits: usize, t: usize, bound: usize) -> Integer {
+    find_prime_with_bit_length_using_sieving_parallel_with_test(bits, bound, &RabinMiller::new(t))
 }
 
 
-pub fn find_prime_with_bit_length_using_sieving(bits: usize, t: usize, bound: usize) -> Integer {
+// Every worker of the rayon pool searches its own random intervals. Once one of them finds a
+// prime no new intervals are started, so the others stop after their current interval.
+pub fn find_prime_with_bit_length_using_sieving_parallel_with_test<T: PrimalityTest + Sync + ?Sized>(bits: usize, bound: usize, test: &T) -> Integer {
     if bound == 0 {
-        find_prime_with_bit_length(bits, t);
+        return (0..u64::MAX).into_par_iter()
+            .find_map_any(|_| {
+                let p = randint_bits_odd(bits);
+                test.is_prime(&p).then_some(p)
+            })
+            .expect("The search only ends when a prime is found");
+    }
+    let probability = 0.95;
+    let d = approx_width_in_random_interval_search(bits, probability).max(1);
+
+    (0..u64::MAX).into_par_iter()
+        .find_map_any(|_| {
+            let (a, d) = random_interval_with_bit_length(bits, d);
+            find_prime_in_interval_with_sieving_with_test(&a, d, bound, test, None)
+        })
+        .expect("The search only ends when a prime is found")
+}
+
+
+// Tests all numbers in parallel, the results are in the same order as the input.
+pub fn is_prime_batch<F>(numbers: &[Integer], is_prime: F) -> Vec<bool>
+where
+    F: Fn(&Integer) -> bool + Sync,
+{
+    numbers.par_iter().map(&is_prime).collect()
+}
+
+
+// Reads one integer per line, as in the "primes" and "non-primes" files, and tests them in
+// parallel.
+pub fn is_prime_batch_from_file<F>(path: &str, is_prime: F) -> Result<Vec<(Integer, bool)>, String>
+where
+    F: Fn(&Integer) -> bool + Sync,
+{
+    let content = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let numbers = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Integer::from_str(line).map_err(|e| format!("Could not parse '{}' in {}: {}", line, path, e)))
+        .collect::<Result<Vec<Integer>, String>>()?;
+
+    let results = is_prime_batch(&numbers, is_prime);
+    Ok(numbers.into_iter().zip(results).collect())
+}
+
+
+// Searches a, a + m, ..., a + (d-1)m for a prime p, which when coprime_to = e is given also has
+// gcd(p - 1, e) = 1. The constraints are handled by the sieve and a gcd, so they cost no extra
+// Rabin-Miller tests.
+pub fn find_prime_in_progression_with_sieving(a: &Integer, m: &Integer, d: usize, t: usize, bound: usize, coprime_to: Option<&Integer>) -> Option<Integer> {
+    for i in sieve_arithmetic_progression(a, m, d, bound, coprime_to) {
+        let p: Integer = a + (m*i as u64).complete();
+        if let Some(e) = coprime_to {
+            if (&p - Integer::ONE).complete().gcd(e) != 1 {
+                continue;
+            }
+        }
+        if rabin_miller_is_prime(&p, t) {
+            return Some(p)
+        }
     }
+
+    None
+}
+
+
+// A prime p of exactly `bits` bits with p = residue (mod modulus), and gcd(p - 1, e) = 1 when e
+// is given.
+pub fn find_prime_with_bit_length_with_constraints(bits: usize, residue: &Integer, modulus: &Integer, coprime_to: Option<&Integer>, t: usize, bound: usize) -> Integer {
+    // p - 1 is even, so it is never coprime to an even e.
+    if coprime_to.is_some_and(|e| e.is_even()) {
+        panic!("No prime p has p - 1 coprime to the even number {}", coprime_to.unwrap());
+    }
+    if residue.gcd_ref(modulus).complete() != 1 {
+        panic!("There is at most one prime congruent to {} modulo {}", residue, modulus);
+    }
+    // Only look at odd candidates, using the Chinese remainder theorem when the modulus is odd.
+    let (residue, modulus) = if modulus.is_odd() {
+        let residue: Integer = residue.clone().modulo(modulus);
+        let residue = if residue.is_odd() { residue } else { residue + modulus };
+        (residue, (modulus*2u32).complete())
+    } else {
+        (residue.clone().modulo(modulus), modulus.clone())
+    };
+
+    let lower: Integer = Integer::from(1) << (bits as u32 - 1);
+    let upper: Integer = Integer::from(1) << bits as u32;
+    let first: Integer = &lower + (&residue - &lower).complete().modulo(&modulus);
+    if first >= upper {
+        panic!("No integer of {} bits is congruent to {} modulo {}", bits, residue, modulus);
+    }
+    let count: Integer = ((&upper - &first).complete() - 1u32) / &modulus + 1u32;
+
     let probability = 0.95;
-    let d = approx_width_in_random_interval_search(bits, probability);
-    
+    let d = approx_width_in_random_interval_search(bits, probability).max(1);
+    let d = count.to_usize().map_or(d, |count| count.min(d));
+    let starts: Integer = count - d + 1;
+
     loop {
-        let a = randint_bits(bits);
-        if let Some(p) = find_prime_in_interval_with_sieving(&a, d, t, bound) {
+        let a: Integer = &first + randint_below(&starts)*&modulus;
+        if let Some(p) = find_prime_in_progression_with_sieving(&a, &modulus, d, t, bound, coprime_to) {
             return p
         }
     }
 }
 
 
+pub fn find_prime_with_bit_length_congruent_to(bits: usize, residue: &Integer, modulus: &Integer, t: usize, bound: usize) -> Integer {
+    find_prime_with_bit_length_with_constraints(bits, residue, modulus, None, t, bound)
+}
+
+
+// A prime p with gcd(p - 1, e) = 1, so that e is a valid RSA exponent modulo p.
+pub fn find_prime_with_bit_length_coprime_to(bits: usize, e: &Integer, t: usize, bound: usize) -> Integer {
+    find_prime_with_bit_length_with_constraints(bits, Integer::ONE, &Integer::from(2), Some(e), t, bound)
+}
+
+
+// A prime p = 3 (mod 4), as used for Blum integers in Rabin and Blum-Blum-Shub.
+pub fn find_blum_prime_with_bit_length(bits: usize, t: usize, bound: usize) -> Integer {
+    find_prime_with_bit_length_congruent_to(bits, &Integer::from(3), &Integer::from(4), t, bound)
+}
+
+
+// Gordon's algorithm for a strong prime p, that is p - 1 has a large prime factor r, p + 1 has a
+// large prime factor s and r - 1 has a large prime factor t:
+//      r = 2it + 1,    p_0 = 2(s^(r-2) mod r)s - 1,    p = p_0 + 2jrs
+// Then p = 1 (mod r) and p = -1 (mod s). s gets bits/2 - 8 bits and t gets bits/2 - 16, so that
+// r ends up a few bits larger than t and 2rs leaves a range of about 2^14 values of j for p to
+// have exactly `bits` bits.
+pub fn find_strong_prime_with_bit_length(bits: usize, coprime_to: Option<&Integer>, t: usize, bound: usize) -> Integer {
+    if bits < 64 {
+        panic!("Strong primes are only generated with at least 64 bits, got {}", bits);
+    }
+    let s = find_prime_with_bit_length_using_sieving(bits/2 - 8, t, bound);
+    let t_prime = find_prime_with_bit_length_using_sieving(bits/2 - 16, t, bound);
+
+    let step: Integer = (&t_prime*2u32).complete();
+    let d = approx_width_in_random_interval_search(bits/2 - 16, 0.95).max(1);
+    let mut a: Integer = (&step + Integer::ONE).complete();
+    let r = loop {
+        if let Some(r) = find_prime_in_progression_with_sieving(&a, &step, d, t, bound, None) {
+            break r
+        }
+        a += (&step*d as u64).complete();
+    };
+
+    let p_0: Integer = 2u32*pow_rug(&s, &(&r - 2u32).complete(), &r)*&s - 1u32;
+    let modulus: Integer = 2u32*(&r*&s).complete();
+    find_prime_with_bit_length_with_constraints(bits, &p_0, &modulus, coprime_to, t, bound)
+}
 
@@ -0,0 +1,280 @@
+use crate::integers::sieve::{integer_sqrt, PrimeTable, SegmentedSieve};
+
+
+// Below this bound pi(x) is counted directly in the prime table.
+const DIRECT_COUNT_LIMIT: u64 = 1 << 22;
+
+// phi(x, c) for the first c = 6 primes is read from a table, using that it is periodic in x with
+// period 2*3*5*7*11*13 = 30030.
+const SMALL_PHI_PRIMES: usize = 6;
+const SMALL_PHI_PERIOD: u64 = 30030;
+
+// y = ALPHA * x^(1/3) balances the number of special leaves against the length of the sieve.
+const ALPHA: u64 = 2;
+
+const EULER_MASCHERONI: f64 = 0.577_215_664_901_532_9;
+
+
+fn integer_root(n: u64, k: u32) -> u64 {
+    let mut root = (n as f64).powf(1./k as f64) as u64;
+    while root > 0 && (root as u128).pow(k) > n as u128 {
+        root -= 1;
+    }
+    while ((root + 1) as u128).pow(k) <= n as u128 {
+        root += 1;
+    }
+    root
+}
+
+
+// phi(x, c) = #{1 <= n <= x : n has no prime factor among the first c primes}, for c <= 6.
+struct SmallPhi {
+    table: Vec<Vec<u32>>,
+}
+
+
+impl SmallPhi {
+    fn new(primes: &[u64]) -> SmallPhi {
+        // table[c][y] = phi(y, c) for y in [0, 30030]
+        let mut table = vec![(0..=SMALL_PHI_PERIOD as u32).collect::<Vec<u32>>()];
+        for c in 1..=SMALL_PHI_PRIMES {
+            let p = primes[c - 1] as usize;
+            let previous = &table[c - 1];
+            let next = (0..=SMALL_PHI_PERIOD as usize).map(|y| previous[y] - previous[y/p]).collect();
+            table.push(next);
+        }
+        SmallPhi { table }
+    }
+
+
+    fn phi(&self, x: u64, c: usize) -> u64 {
+        let period = self.table[c][SMALL_PHI_PERIOD as usize] as u64;
+        (x/SMALL_PHI_PERIOD)*period + self.table[c][(x % SMALL_PHI_PERIOD) as usize] as u64
+    }
+}
+
+
+// Counts the unsieved numbers of a segment, supporting removal of a number and prefix counts in
+// logarithmic time.
+struct FenwickTree {
+    tree: Vec<u32>,
+}
+
+
+impl FenwickTree {
+    fn from_sieve(sieve: &[bool]) -> FenwickTree {
+        let mut tree: Vec<u32> = sieve.iter().map(|unsieved| *unsieved as u32).collect();
+        for i in 0..tree.len() {
+            let parent = i | (i + 1);
+            if parent < tree.len() {
+                tree[parent] += tree[i];
+            }
+        }
+        FenwickTree { tree }
+    }
+
+
+    fn remove(&mut self, mut i: usize) {
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i |= i + 1;
+        }
+    }
+
+
+    // The number of unsieved positions in [0, i].
+    fn count(&self, i: usize) -> u64 {
+        let mut count = 0;
+        let mut i = i as isize;
+        while i >= 0 {
+            count += self.tree[i as usize] as u64;
+            i = (i & (i + 1)) - 1;
+        }
+        count
+    }
+}
+
+
+// The Lagarias-Miller-Odlyzko method. With y = ALPHA x^(1/3) and a = pi(y),
+//      pi(x) = phi(x, a) + a - 1 - P2(x, a),    P2(x, a) = sum_{y < p <= sqrt(x)} (pi(x/p) - pi(p) + 1)
+// Expanding phi(x, b) = phi(x, b - 1) - phi(x/p_b, b - 1) gives the ordinary leaves
+// mu(n) phi(x/n, c) for n <= y, and the special leaves -mu(m) phi(x/(p_b m), b - 1) for
+// m <= y < p_b m with lpf(m) > p_b. The special leaves and P2 only need phi and pi below x/y,
+// which are counted while sieving [1, x/y] by one prime at a time.
+fn lagarias_miller_odlyzko(x: u64) -> u64 {
+    let y = (ALPHA*integer_root(x, 3)).min(integer_sqrt(x)).max(integer_root(x, 3) + 1);
+    let sqrt_x = integer_sqrt(x);
+    let primes = PrimeTable::up_to(sqrt_x);
+    let primes = primes.as_slice();
+    let a = primes.partition_point(|p| *p <= y);
+    let small_phi = SmallPhi::new(primes);
+    let c = SMALL_PHI_PRIMES;
+
+    // Least prime factor and Mobius function up to y.
+    let mut least_prime_factor: Vec<u64> = vec![u64::MAX; y as usize + 1];
+    let mut mobius: Vec<i8> = vec![1; y as usize + 1];
+    for p in primes[..a].iter().rev() {
+        for multiple in (*p as usize..=y as usize).step_by(*p as usize) {
+            least_prime_factor[multiple] = *p;
+            mobius[multiple] = -mobius[multiple];
+        }
+        let square = (p*p) as usize;
+        for multiple in (square..=y as usize).step_by(square) {
+            mobius[multiple] = 0;
+        }
+    }
+
+    let mut phi: i128 = 0;
+    for n in 1..=y as usize {
+        if mobius[n] != 0 && least_prime_factor[n] > primes[c - 1] {
+            phi += mobius[n] as i128*small_phi.phi(x/n as u64, c) as i128;
+        }
+    }
+
+    // The P2 values x/p in increasing order.
+    let p2_primes: Vec<u64> = primes[a..].iter().rev().copied().collect();
+    let mut p2_index = 0;
+    let mut p2: i128 = 0;
+
+    let z = x/y + 1;
+    let segment_size = (integer_sqrt(z) as usize).next_power_of_two().max(1 << 16);
+    // carry[b] = phi(low - 1, b - 1) at the start of each segment, and carry[a + 1] counts what
+    // is left after all a primes are removed.
+    let mut carry: Vec<u64> = vec![0; a + 2];
+
+    let mut low = 1;
+    while low < z {
+        let high = z.min(low + segment_size as u64);
+        let length = (high - low) as usize;
+        let mut sieve = vec![true; length];
+        for p in primes[..c].iter() {
+            let start = low.div_ceil(*p)*p;
+            for multiple in (start..high).step_by(*p as usize) {
+                sieve[(multiple - low) as usize] = false;
+            }
+        }
+        let mut counter = FenwickTree::from_sieve(&sieve);
+
+        for b in c+1..=a {
+            let p = primes[b - 1];
+            let m_min = (y/p).max(x/(p*high));
+            let m_max = y.min(x/(p*low));
+            for m in m_min+1..=m_max {
+                let m_index = m as usize;
+                if mobius[m_index] != 0 && least_prime_factor[m_index] > p {
+                    let v = x/(p*m);
+                    let phi_v = carry[b] + counter.count((v - low) as usize);
+                    phi -= mobius[m_index] as i128*phi_v as i128;
+                }
+            }
+
+            carry[b] += counter.count(length - 1);
+            let start = low.div_ceil(p)*p;
+            for multiple in (start..high).step_by(p as usize) {
+                let index = (multiple - low) as usize;
+                if sieve[index] {
+                    sieve[index] = false;
+                    counter.remove(index);
+                }
+            }
+        }
+
+        // Below y^2 > x/y only 1 and the primes above y are left, so pi(v) = phi(v, a) + a - 1.
+        while p2_index < p2_primes.len() && x/p2_primes[p2_index] < high {
+            let p = p2_primes[p2_index];
+            let pi_v = carry[a + 1] + counter.count((x/p - low) as usize) + a as u64 - 1;
+            let pi_p = (a + p2_primes.len() - p2_index) as u64;
+            p2 += pi_v as i128 - pi_p as i128 + 1;
+            p2_index += 1;
+        }
+        carry[a + 1] += counter.count(length - 1);
+
+        low = high;
+    }
+
+    (phi + a as i128 - 1 - p2) as u64
+}
+
+
+// The number of primes p <= x.
+pub fn prime_pi(x: u64) -> u64 {
+    if x <= DIRECT_COUNT_LIMIT {
+        return PrimeTable::up_to(x).len() as u64;
+    }
+    lagarias_miller_odlyzko(x)
+}
+
+
+// The n'th prime, with p_1 = 2. Beyond the prime table, pi is evaluated just below li^-1(n) and
+// the remaining primes are sieved.
+pub fn nth_prime(n: u64) -> u64 {
+    if n == 0 {
+        panic!("There is no 0th prime");
+    }
+    let estimate = li_inverse(n as f64);
+    if estimate <= DIRECT_COUNT_LIMIT as f64 {
+        return PrimeTable::first(n as usize).largest().expect("There are at least n primes");
+    }
+
+    // |pi(x) - li(x)| is much smaller than sqrt(x) in this range.
+    let margin = estimate.sqrt() as u64;
+    let mut low = (estimate as u64).saturating_sub(margin);
+    let mut count = prime_pi(low);
+    while count >= n {
+        low = low.saturating_sub(margin);
+        count = prime_pi(low);
+    }
+
+    let mut high = low.saturating_add(2*margin);
+    loop {
+        for p in SegmentedSieve::new(low + 1, high + 1) {
+            count += 1;
+            if count == n {
+                return p;
+            }
+        }
+        (low, high) = (high, high.saturating_add(2*margin));
+    }
+}
+
+
+// The logarithmic integral li(x) = int_0^x dt/ln(t), by Ramanujan's series
+//      li(x) = gamma + ln ln x + sqrt(x) sum_{n>=1} (-1)^(n-1) (ln x)^n/(n! 2^(n-1)) sum_{k=0}^{(n-1)/2} 1/(2k+1)
+pub fn li(x: f64) -> f64 {
+    if x == 1. {
+        return f64::NEG_INFINITY;
+    }
+    let ln_x = x.ln();
+    let mut sum = 0.;
+    let mut inner = 0.;
+    let mut term = 1.;
+    for n in 1..200 {
+        term *= ln_x/n as f64;
+        if n > 1 {
+            term /= -2.;
+        }
+        if n % 2 == 1 {
+            inner += 1./(n as f64);
+        }
+        let next = sum + term*inner;
+        if next == sum {
+            break;
+        }
+        sum = next;
+    }
+    EULER_MASCHERONI + ln_x.abs().ln() + x.sqrt()*sum
+}
+
+
+// The x >= 2 with li(x) = y, by Newton's method since li'(x) = 1/ln(x).
+pub fn li_inverse(y: f64) -> f64 {
+    let mut x = (y*y.max(2.).ln()).max(2.);
+    for _ in 0..100 {
+        let next = (x - (li(x) - y)*x.ln()).max(2.);
+        if (next - x).abs() <= 1e-12*x {
+            return next;
+        }
+        x = next;
+    }
+    x
+}
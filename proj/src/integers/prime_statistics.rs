@@ -0,0 +1,89 @@
+use std::time::Duration;
+use crate::integers::sieve::PrimeTable;
+
+
+// Collected by the prime generators when they are given Some(&mut statistics). The counts and
+// times are added to, so one collector can be passed to many searches and averaged afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct PrimeSearchStatistics {
+    // Numbers handed to the primality test, including those rejected by trial division.
+    pub candidates: usize,
+    pub rejected_by_trial_division: usize,
+    // Numbers in a sieved interval which never reached the primality test.
+    pub rejected_by_sieving: usize,
+    pub rabin_miller_rounds: usize,
+    pub intervals: usize,
+    pub searches: usize,
+    pub sieving_time: Duration,
+    pub trial_division_time: Duration,
+    pub primality_test_time: Duration,
+}
+
+
+impl PrimeSearchStatistics {
+    pub fn new() -> PrimeSearchStatistics {
+        PrimeSearchStatistics::default()
+    }
+
+
+    pub fn merge(&mut self, other: &PrimeSearchStatistics) {
+        self.candidates += other.candidates;
+        self.rejected_by_trial_division += other.rejected_by_trial_division;
+        self.rejected_by_sieving += other.rejected_by_sieving;
+        self.rabin_miller_rounds += other.rabin_miller_rounds;
+        self.intervals += other.intervals;
+        self.searches += other.searches;
+        self.sieving_time += other.sieving_time;
+        self.trial_division_time += other.trial_division_time;
+        self.primality_test_time += other.primality_test_time;
+    }
+
+
+    pub fn total_time(&self) -> Duration {
+        self.sieving_time + self.trial_division_time + self.primality_test_time
+    }
+
+
+    pub fn average_candidates(&self) -> f64 {
+        self.candidates as f64/self.searches.max(1) as f64
+    }
+}
+
+
+// A random integer of `bits` bits is prime with probability about 1/ln(2^bits).
+pub fn expected_candidates(bits: usize) -> f64 {
+    (bits as f64)*2f64.ln()
+}
+
+
+// Removing the multiples of the primes below `bound` leaves a fraction prod (1 - 1/p) of the
+// candidates, and the primes are among those left.
+pub fn expected_candidates_with_filtration(bits: usize, bound: usize) -> f64 {
+    let mut prod: f64 = 1.;
+
+    for p in PrimeTable::up_to(bound as u64).iter() {
+        if (p as usize) < bound {
+            prod *= (p as f64 - 1.)/(p as f64);
+        }
+    }
+
+    expected_candidates(bits)*prod
+}
+
+
+// The smallest d such that an interval of d random integers of `bits` bits contains a prime with
+// at least the given probability.
+pub fn width_in_random_interval_search(bits: usize, probability: f64) -> usize {
+    let mut d = 1;
+    while (1f64 - 1f64/expected_candidates(bits)).powf(d as f64) > 1f64 - probability {
+        d += 1;
+    }
+    d
+}
+
+
+// Solves (1 - 1/ln(2^bits))^d = 1 - probability for d, approximating ln(1 - probability) by
+// -probability.
+pub fn approx_width_in_random_interval_search(bits: usize, probability: f64) -> usize {
+    (-probability/(1f64 - 1f64/expected_candidates(bits)).ln()) as usize
+}
@@ -0,0 +1,109 @@
+use std::collections::{BTreeSet, HashMap};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use rayon::prelude::*;
+use rug::Integer;
+use crate::integers::arithmetic_functions::divisors;
+use crate::integers::integer_computations::mul_mod_u64;
+use crate::integers::prime::{lucas_is_prime, rabin_miller_is_prime_u64, rabin_miller_is_prime_with_bases_u64, strong_lucas_is_prime};
+
+
+// Composites which pass the probable prime tests, for testing the tests on more than the easy
+// non-primes p + k.
+
+
+// Chernick: if 6k+1, 12k+1 and 18k+1 are all prime, their product n is a Carmichael number, as
+// n - 1 = 36k(36k^2 + 11k + 1) is divisible by each p - 1. More generally
+//      M_m(k) = (6k+1)(12k+1) prod_{i=1}^{m-2} (9*2^i*k + 1)
+// is a Carmichael number with m prime factors when they are all prime and 2^(m-4) | k.
+// Returns the first `count` of these in increasing order of k.
+pub fn chernick_carmichael_numbers(factors: usize, count: usize) -> Vec<Integer> {
+    if factors < 3 {
+        panic!("Carmichael numbers have at least 3 prime factors");
+    }
+    let step = 1u64 << factors.saturating_sub(4);
+    let mut carmichael_numbers = Vec::with_capacity(count);
+
+    let mut k = step;
+    while carmichael_numbers.len() < count {
+        let mut primes = vec![6*k + 1, 12*k + 1];
+        primes.extend((1..factors as u32 - 1).map(|i| 9*(1 << i)*k + 1));
+        if primes.iter().all(|p| rabin_miller_is_prime_u64(*p)) {
+            carmichael_numbers.push(primes.iter().map(|p| Integer::from(*p)).product());
+        }
+        k += step;
+    }
+
+    carmichael_numbers
+}
+
+
+// Erdős: let P be the primes p not dividing L with p - 1 | L. A product n of primes in P with
+// n = 1 (mod L) is a Carmichael number by Korselt's criterion, since p - 1 | L | n - 1 for each
+// p | n. A random subset S of P is completed by the p in P with p = prod S^-1 (mod L), if there
+// is one. At most `count` distinct numbers are returned, fewer if `attempts` subsets do not give
+// them, which happens when L has too few divisors.
+pub fn erdos_carmichael_numbers(l: u64, count: usize, attempts: usize) -> Vec<Integer> {
+    let primes: Vec<u64> = divisors(&Integer::from(l))
+        .map(|d| d.to_u64().expect("Divisors of L fit in u64") + 1)
+        .filter(|p| !l.is_multiple_of(*p) && rabin_miller_is_prime_u64(*p))
+        .collect();
+    if primes.len() < 3 {
+        return Vec::new();
+    }
+    let by_residue: HashMap<u64, u64> = primes.iter().map(|p| (p % l, *p)).collect();
+
+    let mut rng = thread_rng();
+    let mut carmichael_numbers = BTreeSet::new();
+    for _ in 0..attempts {
+        if carmichael_numbers.len() == count {
+            break;
+        }
+        let size = rng.gen_range(2..primes.len());
+        let subset: Vec<u64> = primes.choose_multiple(&mut rng, size).copied().collect();
+        let residue = subset.iter().fold(1 % l, |product, p| mul_mod_u64(product, *p, l));
+
+        let inverse = match Integer::from(residue).invert(&Integer::from(l)) {
+            Ok(inverse) => inverse.to_u64().expect("Reduced modulo L"),
+            Err(_) => continue,
+        };
+        if let Some(p) = by_residue.get(&inverse) {
+            if !subset.contains(p) {
+                let n: Integer = subset.iter().chain([p]).map(|q| Integer::from(*q)).product();
+                carmichael_numbers.insert(n);
+            }
+        }
+    }
+
+    carmichael_numbers.into_iter().collect()
+}
+
+
+// The odd composites n < bound which are strong probable primes to every base in `bases`, e.g.
+// 2047 for the base 2 and 1373653 for the bases 2, 3.
+pub fn strong_pseudoprimes(bases: &[u64], bound: u64) -> Vec<u64> {
+    odd_composites_passing(bound, |n| rabin_miller_is_prime_with_bases_u64(n, bases))
+}
+
+
+// The odd composites n < bound passing the Lucas test with Selfridge's parameters, or the strong
+// Lucas test if `strong`.
+pub fn lucas_pseudoprimes(bound: u64, strong: bool) -> Vec<u64> {
+    odd_composites_passing(bound, |n| {
+        let n = Integer::from(n);
+        if strong {
+            strong_lucas_is_prime(&n)
+        } else {
+            lucas_is_prime(&n)
+        }
+    })
+}
+
+
+// The cheaper test is run first, as nearly all candidates fail it.
+fn odd_composites_passing<F: Fn(u64) -> bool + Sync>(bound: u64, test: F) -> Vec<u64> {
+    (4..bound.saturating_sub(1).div_ceil(2))
+        .into_par_iter()
+        .map(|i| 2*i + 1)
+        .filter(|n| test(*n) && !rabin_miller_is_prime_u64(*n))
+        .collect()
+}
@@ -0,0 +1,210 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+
+const SEGMENT_SIZE: u64 = 1 << 18;
+
+
+// Every prime up to `bound`, shared by the whole process and only ever extended.
+struct SievedPrimes {
+    bound: u64,
+    primes: Arc<Vec<u64>>,
+}
+
+
+static SIEVED_PRIMES: OnceLock<RwLock<SievedPrimes>> = OnceLock::new();
+
+
+fn sieved_primes() -> &'static RwLock<SievedPrimes> {
+    SIEVED_PRIMES.get_or_init(|| RwLock::new(SievedPrimes { bound: 1, primes: Arc::new(Vec::new()) }))
+}
+
+
+pub fn integer_sqrt(n: u64) -> u64 {
+    let mut root = (n as f64).sqrt() as u128;
+    while root*root > n as u128 {
+        root -= 1;
+    }
+    while (root+1)*(root+1) <= n as u128 {
+        root += 1;
+    }
+    root as u64
+}
+
+
+fn simple_sieve(limit: u64) -> Vec<u64> {
+    let limit = limit as usize;
+    let mut is_prime = vec![true; limit+1];
+    let mut primes = Vec::new();
+    for i in 2..=limit {
+        if is_prime[i] {
+            primes.push(i as u64);
+            for j in (i*i..=limit).step_by(i) {
+                is_prime[j] = false;
+            }
+        }
+    }
+    primes
+}
+
+
+// A view of the first `len` primes of the process-wide table. Cloning only clones the pointer.
+#[derive(Debug, Clone)]
+pub struct PrimeTable {
+    primes: Arc<Vec<u64>>,
+    len: usize,
+}
+
+
+impl PrimeTable {
+    // All primes p <= bound. The first call sieves, later calls with a smaller bound are free.
+    pub fn up_to(bound: u64) -> PrimeTable {
+        {
+            let cache = sieved_primes().read().expect("Prime table lock should not be poisoned");
+            if cache.bound >= bound {
+                let len = cache.primes.partition_point(|p| *p <= bound);
+                return PrimeTable { primes: cache.primes.clone(), len };
+            }
+        }
+
+        let mut cache = sieved_primes().write().expect("Prime table lock should not be poisoned");
+        if cache.bound < bound {
+            // Sieve at least twice as far as before, so that growing the table in many small
+            // steps stays linear.
+            let new_bound = bound.max(cache.bound.saturating_mul(2)).min(u64::MAX - 1);
+            let base_primes = simple_sieve(integer_sqrt(new_bound));
+            let base_primes = PrimeTable { len: base_primes.len(), primes: Arc::new(base_primes) };
+
+            let mut primes: Vec<u64> = cache.primes.as_ref().clone();
+            primes.extend(SegmentedSieve::with_base_primes(cache.bound + 1, new_bound + 1, base_primes));
+            cache.primes = Arc::new(primes);
+            cache.bound = new_bound;
+        }
+
+        let len = cache.primes.partition_point(|p| *p <= bound);
+        PrimeTable { primes: cache.primes.clone(), len }
+    }
+
+
+    // The first n primes, using p_n < n(ln n + ln ln n) for n >= 6 as the first guess.
+    pub fn first(n: usize) -> PrimeTable {
+        let x = n.max(6) as f64;
+        let mut bound = (x*(x.ln() + x.ln().ln())) as u64;
+        loop {
+            let table = PrimeTable::up_to(bound);
+            if table.len >= n {
+                return PrimeTable { primes: table.primes, len: n };
+            }
+            bound *= 2;
+        }
+    }
+
+
+    pub fn as_slice(&self) -> &[u64] {
+        &self.primes[..self.len]
+    }
+
+
+    pub fn iter(&self) -> std::iter::Copied<std::slice::Iter<'_, u64>> {
+        self.as_slice().iter().copied()
+    }
+
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+
+    pub fn largest(&self) -> Option<u64> {
+        self.as_slice().last().copied()
+    }
+
+
+    pub fn contains(&self, n: u64) -> bool {
+        self.as_slice().binary_search(&n).is_ok()
+    }
+}
+
+
+// Iterates over the primes in [low, high) by sieving one segment of SEGMENT_SIZE integers at a
+// time, so the memory used does not depend on the size of the interval.
+#[derive(Debug, Clone)]
+pub struct SegmentedSieve {
+    base_primes: PrimeTable,
+    low: u64,
+    high: u64,
+    segment: Vec<u64>,
+    position: usize,
+}
+
+
+impl SegmentedSieve {
+    pub fn new(low: u64, high: u64) -> SegmentedSieve {
+        let base_primes = PrimeTable::up_to(integer_sqrt(high.saturating_sub(1)));
+        SegmentedSieve::with_base_primes(low, high, base_primes)
+    }
+
+
+    fn with_base_primes(low: u64, high: u64, base_primes: PrimeTable) -> SegmentedSieve {
+        SegmentedSieve {
+            base_primes,
+            low: low.max(2),
+            high,
+            segment: Vec::new(),
+            position: 0,
+        }
+    }
+
+
+    fn sieve_next_segment(&mut self) {
+        let segment_low = self.low;
+        let segment_high = self.high.min(segment_low.saturating_add(SEGMENT_SIZE));
+        let mut is_prime = vec![true; (segment_high - segment_low) as usize];
+
+        for p in self.base_primes.iter() {
+            if p*p >= segment_high {
+                break;
+            }
+            let mut multiple = (p*p).max(segment_low.div_ceil(p)*p);
+            while multiple < segment_high {
+                is_prime[(multiple - segment_low) as usize] = false;
+                multiple += p;
+            }
+        }
+
+        self.segment = is_prime.iter()
+            .enumerate()
+            .filter(|(_, is_prime)| **is_prime)
+            .map(|(i, _)| segment_low + i as u64)
+            .collect();
+        self.position = 0;
+        self.low = segment_high;
+    }
+}
+
+
+impl Iterator for SegmentedSieve {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            if self.position < self.segment.len() {
+                self.position += 1;
+                return Some(self.segment[self.position - 1]);
+            }
+            if self.low >= self.high {
+                return None;
+            }
+            self.sieve_next_segment();
+        }
+    }
+}
+
+
+pub fn primes_in_range(low: u64, high: u64) -> SegmentedSieve {
+    SegmentedSieve::new(low, high)
+}
@@ -0,0 +1,121 @@
+use rand::{thread_rng, Rng};
+use rug::{Complete, Integer};
+use crate::integers::integer_computations::pow_rug;
+use crate::integers::prime::rabin_miller_is_prime_u64;
+use crate::random::randint_bits_odd;
+
+
+// Deterministic tests for numbers of a special form, each costing about one modular
+// exponentiation, where Rabin-Miller needs one per round to be convinced of a prime.
+
+
+pub fn mersenne_number(p: u32) -> Integer {
+    (Integer::from(1) << p) - 1u32
+}
+
+
+pub fn fermat_number(n: u32) -> Integer {
+    (Integer::from(1) << (1u32 << n)) + 1u32
+}
+
+
+pub fn proth_number(k: &Integer, n: u32) -> Integer {
+    (k << n).complete() + 1u32
+}
+
+
+// x mod 2^p - 1 for x >= 0, using 2^p = 1 so that the bits above p can be added to the lower ones.
+fn reduce_mod_mersenne(mut x: Integer, p: u32, m: &Integer) -> Integer {
+    while x.significant_bits() > p {
+        let high = (&x >> p).complete();
+        x.keep_bits_mut(p);
+        x += high;
+    }
+    if x == *m {
+        x = Integer::ZERO.clone();
+    }
+    x
+}
+
+
+// Lucas-Lehmer: for p an odd prime, M_p = 2^p - 1 is prime if and only if s_(p-2) = 0 (mod M_p),
+// where s_0 = 4 and s_(i+1) = s_i^2 - 2. M_p is composite for p composite.
+pub fn lucas_lehmer_is_prime(p: u32) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if !rabin_miller_is_prime_u64(p as u64) {
+        return false;
+    }
+
+    let m = mersenne_number(p);
+    let mut s = Integer::from(4);
+    for _ in 0..p-2 {
+        s = s.square() - 2u32;
+        if s < 0 {
+            s += &m;
+        }
+        s = reduce_mod_mersenne(s, p, &m);
+    }
+    s.is_zero()
+}
+
+
+// The exponents p <= bound of the Mersenne primes 2^p - 1.
+pub fn mersenne_prime_exponents(bound: u32) -> Vec<u32> {
+    (2..=bound).filter(|p| lucas_lehmer_is_prime(*p)).collect()
+}
+
+
+// Pepin: for n >= 1, F_n = 2^(2^n) + 1 is prime if and only if 3^((F_n - 1)/2) = -1 (mod F_n).
+pub fn pepin_is_prime(n: u32) -> bool {
+    if n == 0 {
+        return true;
+    }
+    let f = fermat_number(n);
+    let exponent = Integer::from(1) << ((1u32 << n) - 1);
+    pow_rug(&Integer::from(3), &exponent, &f) == (&f - 1u32).complete()
+}
+
+
+// Proth: for N = k*2^n + 1 with k odd and k < 2^n, N is prime if and only if
+// a^((N-1)/2) = -1 (mod N) for some a. Any a with Jacobi symbol (a/N) = -1 decides it, since for
+// N prime a^((N-1)/2) = (a/N) by Euler's criterion.
+pub fn proth_is_prime(k: &Integer, n: u32) -> bool {
+    if k.is_even() || *k <= 0 || k.significant_bits() > n {
+        panic!("{}*2^{} + 1 is not a Proth number", k, n);
+    }
+    let proth = proth_number(k, n);
+    if proth.is_perfect_square() {
+        return false;
+    }
+
+    let mut a = Integer::from(2);
+    loop {
+        match a.jacobi(&proth) {
+            -1 => break,
+            0 => return false,
+            _ => a += 1,
+        }
+    }
+
+    let n_minus_one = (&proth - 1u32).complete();
+    pow_rug(&a, &(&n_minus_one >> 1u32).complete(), &proth) == n_minus_one
+}
+
+
+// A Proth prime k*2^n + 1 of exactly `bits` bits, with k odd of at most half the bits.
+pub fn find_proth_prime_with_bit_length(bits: usize) -> Integer {
+    if bits < 2 {
+        panic!("There are no Proth primes with less than 2 bits");
+    }
+    let mut rng = thread_rng();
+    loop {
+        let k_bits = rng.gen_range(1..=(bits/2).max(1));
+        let n = (bits - k_bits) as u32;
+        let k = if k_bits == 1 { Integer::from(1) } else { randint_bits_odd(k_bits) };
+        if proth_is_prime(&k, n) {
+            return proth_number(&k, n);
+        }
+    }
+}
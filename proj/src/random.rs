@@ -31,3 +31,23 @@ pub fn randint_digits(digits: usize) -> Integer {
     }
     n
 }
+
+
+// Uniform in [0, n) by rejection sampling of random integers with as many bits as n.
+pub fn randint_below(n: &Integer) -> Integer {
+    if *n <= 0 {
+        panic!("Can't sample below a non-positive bound");
+    }
+    let bits = n.significant_bits() as usize;
+    let mut rng = thread_rng();
+    loop {
+        let mut m = Integer::ZERO.clone();
+        for _ in 0..bits {
+            m <<= 1;
+            m += rng.gen_range(0..=1);
+        }
+        if m < *n {
+            return m;
+        }
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod arithmetic_functions {
+    use rug::{ops::Pow, Integer};
+    use beralg::integers::arithmetic_functions::*;
+    use beralg::factor::factorization::factorize;
+
+    fn naive_divisors(n: u64) -> Vec<u64> {
+        (1..=n).filter(|d| n.is_multiple_of(*d)).collect()
+    }
+
+    fn naive_gcd(a: u64, b: u64) -> u64 {
+        if b == 0 { a } else { naive_gcd(b, a % b) }
+    }
+
+    #[test]
+    fn test_factorize() {
+        for n in 1..2000u64 {
+            let factors = factorize(&Integer::from(n));
+            let mut product = Integer::from(1);
+            for (p, e) in &factors {
+                assert!(p.is_probably_prime(30) != rug::integer::IsPrime::No, "{} is not prime", p);
+                product *= p.clone().pow(*e);
+            }
+            assert_eq!(product, n);
+        }
+
+        let n = Integer::from(1000003u64).pow(3) * Integer::from(8);
+        assert_eq!(factorize(&n), vec![(Integer::from(2), 3), (Integer::from(1000003), 3)]);
+
+        // Cofactors below 2^62 are split by the native methods, and p*q with p and q close to each
+        // other by Fermat's method.
+        let r = Integer::from(2147483647u64);
+        let s = Integer::from(1u64 << 30).next_prime();
+        assert_eq!(factorize(&(r.clone()*&s)), vec![(s, 1), (r, 1)]);
+        let p = Integer::from(2).pow(255).next_prime();
+        let q = (p.clone() + 1000u32).next_prime();
+        assert_eq!(factorize(&(p.clone()*&q)), vec![(p, 1), (q, 1)]);
+    }
+
+    #[test]
+    fn test_arithmetic_functions_against_naive() {
+        for n in 1..500u64 {
+            let integer = Integer::from(n);
+            let divs = naive_divisors(n);
+            let coprime = (1..=n).filter(|a| naive_gcd(*a, n) == 1).collect::<Vec<u64>>();
+
+            let phi = coprime.len() as u64;
+            let lambda = (1..=n)
+                .find(|k| coprime.iter().all(|a| Integer::from(*a).pow_mod(&Integer::from(*k), &integer).unwrap() == Integer::from(1) % n))
+                .unwrap();
+
+            assert_eq!(euler_phi(&integer), phi, "phi({})", n);
+            assert_eq!(carmichael_lambda(&integer), lambda, "lambda({})", n);
+            assert_eq!(sum_of_divisors(&integer), divs.iter().sum::<u64>(), "sigma({})", n);
+            assert_eq!(number_of_divisors(&integer), divs.len() as u64, "tau({})", n);
+
+            let mut found: Vec<u64> = divisors(&integer).map(|d| d.to_u64().unwrap()).collect();
+            found.sort();
+            assert_eq!(found, divs, "divisors of {}", n);
+
+            let squarefree = (2..=n).all(|d| n % (d*d) != 0);
+            let omega = divs.iter().filter(|d| **d > 1 && naive_divisors(**d).len() == 2).count();
+            let mu = if !squarefree { 0 } else if omega % 2 == 0 { 1 } else { -1 };
+            assert_eq!(mobius(&integer), mu, "mu({})", n);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod integer_computations {
+    use rug::Integer;
+    use beralg::integers::integer_computations::{mul_mod_u64, sqrt_mod_u64};
+    use beralg::integers::sieve::PrimeTable;
+
+    #[test]
+    fn test_sqrt_mod() {
+        // 65537 = 2^16 + 1, 998244353 = 119*2^23 + 1 and 3221225473 = 3*2^30 + 1 have a large power
+        // of two dividing p - 1, which Tonelli-Shanks has to go through.
+        for p in PrimeTable::first(300).iter().skip(1).chain([65537, 998244353, 3221225473]) {
+            let mut residues = 0;
+            for a in (0..p).step_by((p/200).max(1) as usize) {
+                match sqrt_mod_u64(a, p) {
+                    Some(x) => {
+                        assert_eq!(mul_mod_u64(x, x, p), a, "Wrong square root {} of {} modulo {}", x, a, p);
+                        residues += 1;
+                    },
+                    None => assert_eq!(Integer::from(a).legendre(&Integer::from(p)), -1),
+                }
+            }
+            assert!(residues > 0);
+        }
+    }
+}
@@ -1,8 +1,16 @@
 #[cfg(test)]
 mod prime {
     use std::{io::{BufRead, BufReader}, str::FromStr, fs};
-    use rug::{Integer, integer::IsPrime};
+    use rug::{Complete, Integer, integer::IsPrime, rand::RandState};
     use beralg::integers::prime::*;
+    use beralg::integers::certificate::*;
+    use beralg::integers::sieve::*;
+    use beralg::integers::group_parameters::*;
+    use beralg::integers::primality_test::*;
+    use beralg::integers::prime_statistics::*;
+    use beralg::integers::prime_counting::*;
+    use beralg::integers::pseudoprimes::*;
+    use beralg::integers::special_form::*;
     use rand::{thread_rng, Rng};
 
     #[test]
@@ -63,4 +71,501 @@ mod prime {
             assert_eq!(sieving.significant_bits(), bits as u32);
         }
     }
+
+    #[test]
+    fn test_deterministic_rabin_miller() {
+        // Strong pseudoprimes to all prime bases up to 7, 23 and 37 respectively.
+        let strong_pseudoprimes = ["3215031751", "3825123056546413051", "318665857834031151167461"];
+        for n in strong_pseudoprimes {
+            let n = Integer::from_str(n).unwrap();
+            assert_eq!(rabin_miller_is_prime_deterministic(&n), Some(false), "Identified {} as prime", &n);
+            if let Some(m) = n.to_u64() {
+                assert!(!rabin_miller_is_prime_u64(m), "Identified {} as prime using u64", &n);
+            }
+        }
+
+        for m in 0..10000u64 {
+            let n = Integer::from(m);
+            assert_eq!(rabin_miller_is_prime_u64(m), n.is_probably_prime(30) != IsPrime::No, "Failed for {}", m);
+        }
+
+        let mut rng = thread_rng();
+        for _ in 0..10000 {
+            let m: u64 = rng.gen();
+            let n = Integer::from(m);
+            assert_eq!(rabin_miller_is_prime_u64(m), n.is_probably_prime(30) != IsPrime::No, "Failed for {}", m);
+
+            let n: Integer = Integer::from(rng.gen::<u64>() >> 16) * Integer::from(rng.gen::<u32>()) + 1;
+            assert_eq!(rabin_miller_is_prime_deterministic(&n), Some(n.is_probably_prime(30) != IsPrime::No), "Failed for {}", &n);
+        }
+
+        let too_large = Integer::from(1) << 100u32;
+        assert_eq!(rabin_miller_is_prime_deterministic(&too_large), None);
+    }
+
+    #[test]
+    fn test_lucas_sequence() {
+        let n = Integer::from(1_000_000_007u64);
+        let (mut f0, mut f1) = (Integer::from(0), Integer::from(1));
+        let (mut l0, mut l1) = (Integer::from(2), Integer::from(1));
+        for k in 0..300u32 {
+            let (u, v) = lucas_sequence(&Integer::from(1), &Integer::from(-1), &Integer::from(k), &n);
+            assert_eq!(u, (&f0 % &n).complete(), "U_{} is not the Fibonacci number", k);
+            assert_eq!(v, (&l0 % &n).complete(), "V_{} is not the Lucas number", k);
+            (f0, f1) = (f1.clone(), f0 + f1);
+            (l0, l1) = (l1.clone(), l0 + l1);
+        }
+    }
+
+    #[test]
+    fn test_baillie_psw() {
+        let strong_lucas_pseudoprimes = [5459u64, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519];
+        for n in strong_lucas_pseudoprimes {
+            assert!(strong_lucas_is_prime(&Integer::from(n)), "{} should pass the strong Lucas test", n);
+            assert!(!baillie_psw_is_prime(&Integer::from(n)), "Identified {} as prime using BPSW", n);
+        }
+
+        for m in 0..20000u64 {
+            let n = Integer::from(m);
+            let is_prime = n.is_probably_prime(30) != IsPrime::No;
+            assert_eq!(baillie_psw_is_prime(&n), is_prime, "Failed for {}", m);
+            if is_prime && m % 2 == 1 {
+                assert!(strong_lucas_is_prime(&n), "Identified {} as non-prime using strong Lucas", m);
+            }
+        }
+
+        let file = fs::File::open("primes").unwrap();
+        for line in BufReader::new(file).lines() {
+            let p: Integer = Integer::from_str(&line.unwrap()).unwrap();
+            assert!(baillie_psw_is_prime(&p), "Identified {} as non-prime using BPSW", &p);
+            let composite: Integer = &p * (&p + Integer::from(2));
+            assert!(!baillie_psw_is_prime(&composite), "Identified {} as prime using BPSW", &composite);
+        }
+
+        let mersenne = (Integer::from(1) << 521u32) - 1;
+        assert!(baillie_psw_is_prime(&mersenne));
+        assert!(!baillie_psw_is_prime(&(mersenne * 3)));
+    }
+
+    #[test]
+    fn test_primality_certificates() {
+        let mut rng = RandState::new();
+
+        // Pratt, where p - 1 is a product of small primes.
+        let p = loop {
+            let mut p = Integer::from(2);
+            while p.significant_bits() < 90 {
+                p *= Integer::from(rng.bits(9)).next_prime();
+            }
+            p += 1;
+            if p.is_probably_prime(30) != IsPrime::No {
+                break p;
+            }
+        };
+        let pratt = pratt_certificate(&p).expect("p - 1 is smooth");
+        assert!(matches!(pratt, PrimalityCertificate::Pratt { .. }));
+        assert!(pratt.verify());
+        assert_eq!(PrimalityCertificate::from_str(&pratt.to_string()), Ok(pratt.clone()));
+
+        // Pocklington, where p = 2kq + 1 with q a large prime.
+        let q = (Integer::from(rng.bits(31)) << 32u32).next_prime();
+        let p = loop {
+            let p: Integer = 2 * Integer::from(rng.bits(14)) * &q + 1;
+            if p.is_probably_prime(30) != IsPrime::No {
+                break p;
+            }
+        };
+        let pocklington = pocklington_certificate(&p).expect("p - 1 has a large prime factor");
+        assert!(pocklington.verify());
+        assert_eq!(PrimalityCertificate::from_str(&pocklington.to_string()), Ok(pocklington.clone()));
+
+        // Brillhart-Lehmer-Selfridge, where only p^(1/3) <= F < p^(1/2) of p - 1 is known.
+        let q = (Integer::from(rng.bits(32)) << 18u32).next_prime();
+        let (p, r) = loop {
+            let r: Integer = 2 * Integer::from(rng.bits(32)) * Integer::from(rng.bits(32)) * Integer::from(rng.bits(16));
+            let p: Integer = (&q * &r).complete() + 1;
+            if p.is_probably_prime(30) != IsPrime::No {
+                break (p, r);
+            }
+        };
+        let bls = pocklington_certificate_from_partial_factorization(&p, &[(Integer::from(2), 1), (q.clone(), 1)])
+            .expect("F^3 >= p");
+        assert!(bls.verify(), "BLS failed for p = {} = {}*{} + 1", &p, &q, &r);
+
+        // Forged certificates are rejected.
+        if let PrimalityCertificate::Pratt { p, factors, .. } = pratt {
+            let forged = PrimalityCertificate::Pratt { p, witness: Integer::from(1), factors };
+            assert!(!forged.verify());
+        }
+        let composite = Integer::from(3215031751u64) * Integer::from(1000000007u64);
+        assert!(certify_prime(&composite).is_none());
+        let forged = PrimalityCertificate::Pocklington { p: composite, factors: Vec::new() };
+        assert!(!forged.verify());
+        assert!(PrimalityCertificate::from_str("pratt 7 3 2\n2 1\nsmall 2").is_err());
+    }
+
+    #[test]
+    fn test_find_provable_prime_with_bit_length() {
+        for bits in [2, 17, 64, 65, 100, 257, 512] {
+            let (p, certificate) = find_provable_prime_with_bit_length(bits);
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert_eq!(certificate.prime(), &p);
+            assert!(certificate.verify(), "Certificate for {} does not verify", &p);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as prime", &p);
+        }
+    }
+
+    #[test]
+    fn test_prime_table_and_segmented_sieve() {
+        let mut p = Integer::from(2);
+        let table = PrimeTable::up_to(200000);
+        for q in table.iter() {
+            assert_eq!(p, q);
+            p.next_prime_mut();
+        }
+        assert!(p > 200000);
+        assert_eq!(PrimeTable::up_to(100).len(), 25);
+        assert_eq!(PrimeTable::first(1000).largest(), Some(7919));
+        assert!(table.contains(199999) && !table.contains(199997));
+
+        let low = 1_000_000_000_000u64;
+        let high = low + 1_000_000;
+        let mut p = Integer::from(low - 1).next_prime();
+        for q in primes_in_range(low, high) {
+            assert_eq!(p, q);
+            p.next_prime_mut();
+        }
+        assert!(p >= high);
+    }
+
+    #[test]
+    fn test_safe_primes_and_schnorr_groups() {
+        for bits in [3, 4, 5, 20, 64, 128, 256] {
+            let p = find_safe_prime_with_bit_length(bits, 30);
+            let q: Integer = (&p - Integer::from(1)) / 2;
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as safe prime", &p);
+            assert!(q.is_probably_prime(30) != IsPrime::No, "Found {} as safe prime, but (p-1)/2 is not prime", &p);
+
+            let q = find_sophie_germain_prime_with_bit_length(bits, 30);
+            assert_eq!(q.significant_bits(), bits as u32);
+            assert!(q.is_probably_prime(30) != IsPrime::No);
+            let p: Integer = 2*q + 1;
+            assert!(p.is_probably_prime(30) != IsPrime::No);
+        }
+
+        let groups = [find_safe_prime_group(128, 30), find_schnorr_group(512, 160, 30), find_schnorr_group(10, 5, 30)];
+        for group in groups {
+            let p = group.mod_num();
+            let subgroup = group.get_subgroup().expect("Should have subgroup data");
+            let (q, g) = (subgroup.get_order(), subgroup.get_generator());
+            assert!(p.is_probably_prime(30) != IsPrime::No);
+            assert!(q.is_probably_prime(30) != IsPrime::No);
+            assert!(group.get_size().is_divisible(q));
+            assert!(*g != 1);
+            assert_eq!(g.clone().pow_mod(q, p).unwrap(), 1);
+        }
+    }
+
+    #[test]
+    fn test_constrained_prime_search() {
+        let is_prime = |p: &Integer| p.is_probably_prime(30) != IsPrime::No;
+        let mut rng = thread_rng();
+        for _ in 0..20 {
+            let bits = rng.gen_range(20..300);
+            let modulus = Integer::from(rng.gen_range(1..1000u32));
+            let residue = loop {
+                let residue = Integer::from(rng.gen_range(0..1000u32));
+                if residue.gcd_ref(&modulus).complete() == 1 {
+                    break residue;
+                }
+            };
+            let p = find_prime_with_bit_length_congruent_to(bits, &residue, &modulus, 30, 100);
+            assert!(is_prime(&p), "Found {} as prime", &p);
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert_eq!((&p - &residue).complete().modulo(&modulus), 0, "{} is not {} mod {}", &p, &residue, &modulus);
+
+            let p = find_blum_prime_with_bit_length(bits, 30, 100);
+            assert!(is_prime(&p) && p.mod_u(4) == 3);
+            assert_eq!(p.significant_bits(), bits as u32);
+
+            let e = Integer::from(rng.gen_range(3..100u32) | 1);
+            let p = find_prime_with_bit_length_coprime_to(bits, &e, 30, 100);
+            assert!(is_prime(&p));
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert_eq!((&p - 1u32).complete().gcd(&e), 1);
+        }
+
+        // gcd(p - 1, 3) = 1 forces p = 2 (mod 3).
+        let three = Integer::from(3);
+        let p = find_prime_with_bit_length_coprime_to(40, &three, 30, 100);
+        assert_eq!(p.mod_u(3), 2);
+
+        for bits in [64, 512, 1024] {
+            let e = Integer::from(65537);
+            let p = find_strong_prime_with_bit_length(bits, Some(&e), 30, 1000);
+            assert!(is_prime(&p));
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert_eq!((&p - 1u32).complete().gcd(&e), 1);
+
+            // Both p - 1 and p + 1 keep a large factor after removing every prime below 2^16.
+            for mut n in [(&p - 1u32).complete(), (&p + 1u32).complete()] {
+                for q in PrimeTable::up_to(1 << 16).iter() {
+                    while n.is_divisible_u(q as u32) {
+                        n /= q;
+                    }
+                }
+                assert!(n.significant_bits() as usize >= (bits - 32)/2, "{} is not a strong prime", &p);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "even number")]
+    fn test_prime_coprime_to_even_number() {
+        find_prime_with_bit_length_coprime_to(40, &Integer::from(6), 30, 100);
+    }
+
+    #[test]
+    fn test_parallel_prime_search_and_batch() {
+        for bits in [5, 64, 512] {
+            for bound in [0, 1000] {
+                let p = find_prime_with_bit_length_using_sieving_parallel(bits, 30, bound);
+                assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as prime", &p);
+                assert_eq!(p.significant_bits(), bits as u32);
+            }
+        }
+
+        let primes = is_prime_batch_from_file("primes", baillie_psw_is_prime).unwrap();
+        assert!(!primes.is_empty());
+        assert!(primes.iter().all(|(_, is_prime)| *is_prime));
+        let non_primes = is_prime_batch_from_file("non-primes", |n| rabin_miller_is_prime(n, 30)).unwrap();
+        assert!(non_primes.iter().all(|(_, is_prime)| !*is_prime));
+        assert!(is_prime_batch_from_file("no-such-file", baillie_psw_is_prime).is_err());
+
+        let numbers: Vec<Integer> = (0..5000u32).map(Integer::from).collect();
+        let results = is_prime_batch(&numbers, baillie_psw_is_prime);
+        for (n, is_prime) in numbers.iter().zip(results) {
+            assert_eq!(is_prime, n.is_probably_prime(30) != IsPrime::No, "Failed for {}", n);
+        }
+    }
+
+    #[test]
+    fn test_primality_test_implementations() {
+        let tests: Vec<Box<dyn PrimalityTest>> = vec![
+            Box::new(Fermat::new(30)),
+            Box::new(RabinMiller::new(30)),
+            Box::new(DeterministicRabinMiller::new(30)),
+            Box::new(BailliePSW),
+            Box::new(TrialDivision::new(100, RabinMiller::new(30))),
+            Box::new(TrialDivision::new(0, BailliePSW)),
+            Box::new(RabinMiller::new(1).and(BailliePSW)),
+        ];
+
+        for test in &tests {
+            for m in 5..3000u64 {
+                let n = Integer::from(m);
+                assert_eq!(test.is_prime(&n), n.is_probably_prime(30) != IsPrime::No, "Failed for {}", m);
+            }
+            let p = find_prime_with_bit_length_with_test(128, test, None);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as prime", &p);
+            assert_eq!(p.significant_bits(), 128);
+        }
+
+        let test = TrialDivision::new(1000, DeterministicRabinMiller::new(30));
+        assert_eq!(test.bound(), 997);
+        for bits in [10, 64, 256] {
+            let p = find_prime_with_bit_length_using_sieving_with_test(bits, 100, &test, None);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as prime with sieving", &p);
+            let p = find_prime_with_bit_length_using_sieving_parallel_with_test(bits, 100, &BailliePSW);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as prime with parallel sieving", &p);
+        }
+
+        // 3215031751 is a strong pseudoprime to the bases 2, 3, 5 and 7, which BPSW still rejects.
+        let pseudoprime = Integer::from(3215031751u64);
+        assert!(!BailliePSW.is_prime(&pseudoprime));
+        assert!(!DeterministicRabinMiller::new(0).is_prime(&pseudoprime));
+    }
+
+    #[test]
+    fn test_prime_search_statistics() {
+        let bits = 128;
+        let searches = 100;
+        let test = TrialDivision::new(1000, RabinMiller::new(30));
+        let mut statistics = PrimeSearchStatistics::new();
+        for _ in 0..searches {
+            find_prime_with_bit_length_with_test(bits, &test, Some(&mut statistics));
+        }
+        assert_eq!(statistics.searches, searches);
+        assert!(statistics.rabin_miller_rounds >= 30*searches);
+
+        // Only odd candidates are drawn, so half of the expected number is needed.
+        let average = statistics.average_candidates();
+        let expected = expected_candidates(bits)/2.;
+        assert!(average > 0.6*expected && average < 1.4*expected, "Average {} is far from {}", average, expected);
+
+        // About 2*e^-gamma/ln(1000) of the odd candidates survive trial division by the primes below 1000.
+        let survived = 1. - statistics.rejected_by_trial_division as f64/statistics.candidates as f64;
+        let expected = expected_candidates_with_filtration(bits, 1000)/expected_candidates(bits)*2.;
+        assert!((survived - expected).abs() < 0.05, "{} survived trial division, expected {}", survived, expected);
+
+        let mut sieving = PrimeSearchStatistics::new();
+        for _ in 0..searches {
+            find_prime_with_bit_length_using_sieving_with_test(bits, 1000, &RabinMiller::new(30), Some(&mut sieving));
+        }
+        assert_eq!(sieving.searches, searches);
+        assert!(sieving.intervals >= searches && sieving.rejected_by_sieving > 0);
+        assert_eq!(sieving.rejected_by_trial_division, 0);
+        assert!(sieving.candidates < statistics.candidates);
+
+        let mut merged = statistics.clone();
+        merged.merge(&sieving);
+        assert_eq!(merged.candidates, statistics.candidates + sieving.candidates);
+        assert!(merged.total_time() >= sieving.total_time());
+
+        assert_eq!(width_in_random_interval_search(bits, 0.95), 265);
+        assert!(approx_width_in_random_interval_search(bits, 0.95) <= width_in_random_interval_search(bits, 0.95));
+    }
+
+    #[test]
+    fn test_exact_bit_length_and_prime_in_range() {
+        for bits in (2..40).chain([64, 65, 128]) {
+            for _ in 0..10 {
+                if let Some(p) = find_prime_with_bit_length_using_interval(bits, 2*bits, 30, 100) {
+                    assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as prime with interval", &p);
+                    assert_eq!(p.significant_bits(), bits as u32);
+                }
+                for bound in [0, 100] {
+                    let p = find_prime_with_bit_length_using_sieving(bits, 30, bound);
+                    assert!(p.is_probably_prime(30) != IsPrime::No, "Found {} as prime with sieving", &p);
+                    assert_eq!(p.significant_bits(), bits as u32);
+                }
+            }
+        }
+
+        assert_eq!(find_prime_in_range(&Integer::from(24), &Integer::from(28), 30, 100), None);
+        assert_eq!(find_prime_in_range(&Integer::from(90), &Integer::from(96), 30, 100), None);
+        assert_eq!(find_prime_in_range(&Integer::from(0), &Integer::from(1), 30, 100), None);
+        assert_eq!(find_prime_in_range(&Integer::from(10), &Integer::from(5), 30, 100), None);
+        assert_eq!(find_prime_in_range(&Integer::from(2), &Integer::from(2), 30, 100), Some(Integer::from(2)));
+        assert_eq!(find_prime_in_range(&Integer::from(89), &Integer::from(96), 30, 100), Some(Integer::from(89)));
+
+        // Each of the 21 primes in [100, 200] should be picked about 100 times.
+        let primes: Vec<u64> = PrimeTable::up_to(200).iter().filter(|p| *p >= 100).collect();
+        let mut counts = vec![0; primes.len()];
+        for _ in 0..100*primes.len() {
+            let p = find_prime_in_range(&Integer::from(100), &Integer::from(200), 30, 100).unwrap();
+            let index = primes.binary_search(&p.to_u64().unwrap()).expect("Should be a prime in the range");
+            counts[index] += 1;
+        }
+        assert!(counts.iter().all(|count| *count > 50 && *count < 160), "Not uniform: {:?}", counts);
+
+        let a = Integer::from(1) << 100u32;
+        let b: Integer = &a + (Integer::from(1) << 40u32);
+        for _ in 0..10 {
+            let p = find_prime_in_range(&a, &b, 30, 1000).unwrap();
+            assert!(a <= p && p <= b);
+            assert!(p.is_probably_prime(30) != IsPrime::No);
+        }
+    }
+
+
+    #[test]
+    fn test_prime_counting() {
+        let known: [u64; 11] = [0, 4, 25, 168, 1229, 9592, 78498, 664579, 5761455, 50847534, 455052511];
+        for (k, pi) in known.iter().enumerate() {
+            assert_eq!(prime_pi(10u64.pow(k as u32)), *pi, "pi(10^{})", k);
+        }
+
+        // Around the switch from the prime table to Lagarias-Miller-Odlyzko.
+        for x in [(1 << 22) - 1, 1 << 22, (1 << 22) + 1, 5000000, 12345678] {
+            assert_eq!(prime_pi(x), PrimeTable::up_to(x).len() as u64, "pi({})", x);
+        }
+
+        let primes = PrimeTable::up_to(10000);
+        for (i, p) in primes.iter().enumerate() {
+            assert_eq!(nth_prime(i as u64 + 1), p);
+        }
+        assert_eq!(nth_prime(1000000), 15485863);
+        assert_eq!(nth_prime(100000000), 2038074743);
+
+        assert!((li(2.) - 1.045163780117492).abs() < 1e-9);
+        assert!((li(1e10) - 455055614.586).abs() < 1e-2);
+        for x in [1e3, 1e6, 1e10] {
+            assert!((li_inverse(li(x)) - x).abs() < 1e-6*x);
+        }
+    }
+
+
+    #[test]
+    fn test_solovay_strassen_and_pseudoprimes() {
+        let t = 30;
+        let primes = PrimeTable::up_to(10000);
+        for n in 0..10000u64 {
+            let is_prime = primes.as_slice().binary_search(&n).is_ok();
+            assert_eq!(solovay_strassen_is_prime(&Integer::from(n), t), is_prime, "Solovay-Strassen failed for {}", n);
+            assert_eq!(lucas_is_prime(&Integer::from(n)), is_prime || [323, 377, 1159, 1829, 3827, 5459, 5777, 9071, 9179].contains(&n), "Lucas test failed for {}", n);
+        }
+        let p = find_prime_with_bit_length(512, t);
+        assert!(SolovayStrassen::new(t).is_prime(&p));
+        assert!(!SolovayStrassen::new(t).is_prime(&(&p*find_prime_with_bit_length(256, t))));
+
+        let mut carmichael_numbers = chernick_carmichael_numbers(3, 10);
+        assert_eq!(&carmichael_numbers[..4], [1729, 294409, 56052361, 118901521]);
+        assert_eq!(chernick_carmichael_numbers(4, 1), [63973]);
+        carmichael_numbers.extend(chernick_carmichael_numbers(5, 5));
+        carmichael_numbers.extend(erdos_carmichael_numbers(720720, 20, 1000000));
+        assert!(carmichael_numbers.len() > 20);
+        for n in carmichael_numbers.iter() {
+            let n_minus_one = (n - 1u32).complete();
+            for a in (2..100u32).map(Integer::from).filter(|a| a.clone().gcd(n) == 1) {
+                assert_eq!(a.pow_mod(&n_minus_one, n).unwrap(), 1, "{} is not a Carmichael number", n);
+            }
+            assert!(!solovay_strassen_is_prime(n, t), "Identified Carmichael number {} as prime using Solovay-Strassen", n);
+            assert!(!rabin_miller_is_prime(n, t), "Identified Carmichael number {} as prime using Rabin-Miller", n);
+            assert!(!baillie_psw_is_prime(n), "Identified Carmichael number {} as prime using Baillie-PSW", n);
+        }
+
+        assert_eq!(strong_pseudoprimes(&[2], 100000), [2047, 3277, 4033, 4681, 8321, 15841, 29341, 42799, 49141, 52633, 65281, 74665, 80581, 85489, 88357, 90751]);
+        assert_eq!(strong_pseudoprimes(&[2, 3], 2000000), [1373653, 1530787, 1987021]);
+        assert_eq!(lucas_pseudoprimes(10000, false), [323, 377, 1159, 1829, 3827, 5459, 5777, 9071, 9179]);
+        assert_eq!(lucas_pseudoprimes(20000, true), [5459, 5777, 10877, 16109, 18971]);
+        for n in strong_pseudoprimes(&[2], 100000) {
+            assert!(!baillie_psw_is_prime(&Integer::from(n)));
+        }
+    }
+
+
+    #[test]
+    fn test_special_form_primality_tests() {
+        assert_eq!(mersenne_prime_exponents(1300), [2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607, 1279]);
+        for p in [2203, 2281] {
+            assert!(lucas_lehmer_is_prime(p));
+        }
+
+        for n in 0..=4 {
+            assert!(pepin_is_prime(n), "F_{} is prime", n);
+        }
+        for n in 5..=12 {
+            assert!(!pepin_is_prime(n), "F_{} is composite", n);
+        }
+
+        let primes = PrimeTable::up_to(100000);
+        for n in 1..16 {
+            for k in (1..1u64 << n).step_by(2) {
+                let proth = proth_number(&Integer::from(k), n);
+                if proth >= 100000 {
+                    break;
+                }
+                let is_prime = primes.as_slice().binary_search(&proth.to_u64().unwrap()).is_ok();
+                assert_eq!(proth_is_prime(&Integer::from(k), n), is_prime, "Proth test failed for {}", proth);
+            }
+        }
+
+        for bits in [2, 3, 5, 10, 64, 256, 1024] {
+            let p = find_proth_prime_with_bit_length(bits);
+            assert_eq!(p.significant_bits() as usize, bits);
+            assert!(baillie_psw_is_prime(&p), "{} should be prime", p);
+        }
+    }
 }